@@ -1,8 +1,7 @@
 use std::path::PathBuf;
 
 use jpreprocess::{JPreprocess, SystemDictionaryConfig};
-use jpreprocess_core::pos::POS;
-use jpreprocess_dictionary::tokenizer::default::DefaultTokenizer;
+use jpreprocess_core::{pos::POS, token::DynTokenizer};
 use jpreprocess_jpcommon::njdnodes_to_features;
 use jpreprocess_njd::NJDNode;
 
@@ -16,7 +15,7 @@ use crate::{into_runtime_error, structs::*};
 
 #[pyclass(name = "JPreprocess")]
 pub struct JPreprocessPyBinding {
-    inner: JPreprocess<DefaultTokenizer>,
+    inner: JPreprocess<Box<dyn DynTokenizer + Send + Sync>>,
 }
 
 #[pymethods]
@@ -45,7 +44,9 @@ impl JPreprocessPyBinding {
                         ))),
                     })
                     .transpose()?,
-            ),
+            )
+            .map_err(into_runtime_error)?
+            .into_dyn(),
         })
     }
     fn run_frontend(&self, text: &str) -> PyResult<Vec<NjdObject>> {
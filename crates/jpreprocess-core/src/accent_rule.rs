@@ -164,6 +164,20 @@ impl ChainRules {
         result
     }
 
+    /// Parses the rules like [`ChainRules::new`], but fails on the first invalid rule
+    /// instead of skipping it.
+    pub fn parse(rules: &str) -> JPreprocessResult<Self> {
+        let mut result = Self::default();
+        if rules.is_empty() || rules == "*" {
+            return Ok(result);
+        }
+
+        for rule in rules.split('/') {
+            result.push_rule(rule)?;
+        }
+        Ok(result)
+    }
+
     fn push_rule(&mut self, rule_str: &str) -> JPreprocessResult<()> {
         let (pos, rule) = Self::parse_rule(rule_str)?;
         match pos {
@@ -268,6 +282,17 @@ mod tests {
         assert_eq!(rules.to_string(), "C3");
     }
 
+    #[test]
+    fn strict_parse() {
+        assert_eq!(
+            ChainRules::parse("形容詞%F2@0/動詞%F5").unwrap(),
+            ChainRules::new("形容詞%F2@0/動詞%F5")
+        );
+        assert_eq!(ChainRules::parse("*").unwrap(), ChainRules::default());
+        assert!(ChainRules::parse("C1/F9").is_err());
+        assert!(ChainRules::parse("副詞%C1").is_err());
+    }
+
     #[test]
     fn single_complex_rule() {
         let rules = ChainRules::new("形容詞%F2@-1");
@@ -12,6 +12,9 @@ pub enum JPreprocessError {
     #[cfg(feature = "lindera")]
     #[error("Lindera error: {0}")]
     LinderaError(#[from] lindera::error::LinderaError),
+    #[cfg(feature = "vibrato")]
+    #[error("Vibrato error: {0}")]
+    VibratoError(#[from] vibrato::errors::VibratoError),
     #[error("Failed to parse pronunciation: {0}")]
     PronunciationParseError(#[from] PronunciationParseError),
     #[error("Failed to parse part of speech (POS): {0}")]
@@ -32,6 +35,12 @@ pub enum DictionaryError {
     IdNotFound(u32),
     #[error("Failed to decode: {0}")]
     FailDecode(#[from] bincode::error::DecodeError),
+    #[error("The dictionary has word format version {found}, but this JPreprocess reads up to version {expected}. Rebuild the dictionary with this JPreprocess.")]
+    UnsupportedFormatVersion { found: u32, expected: u32 },
+    #[error("The dictionary was built by JPreprocess {0}, whose word format cannot be read by this JPreprocess. Rebuild the dictionary with this JPreprocess.")]
+    UnsupportedLegacyVersion(String),
+    #[error("The dictionary identifier {0:?} has no valid word format version")]
+    InvalidFormatIdentifier(String),
     #[error("The word is flagged as UserDictionary, but Lindera UserDictionary is empty")]
     UserDictionaryNotProvided,
     #[error("The word is flagged as UserDictionary, but UserDictionary mode is not set")]
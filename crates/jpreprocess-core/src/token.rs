@@ -6,6 +6,111 @@ pub trait Tokenizer {
 
 pub trait Token {
     fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)>;
+
+    /// Returns where the token came from and its lattice cost, if the tokenizer knows them.
+    fn metadata(&self) -> Option<TokenMetadata> {
+        None
+    }
+}
+
+/// An object-safe version of [`Tokenizer`].
+///
+/// Every [`Tokenizer`] implements this trait, and `Box<dyn DynTokenizer>` implements [`Tokenizer`],
+/// so the tokenizer can be chosen at runtime:
+///
+/// ```rust
+/// # use jpreprocess_core::{token::*, word_entry::WordEntry, JPreprocessResult};
+/// struct Whitespace;
+/// impl Tokenizer for Whitespace {
+///     fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+///         Ok(text
+///             .split_whitespace()
+///             .map(|s| (s.to_string(), WordEntry::default()))
+///             .collect::<Vec<_>>())
+///     }
+/// }
+///
+/// let tokenizer: Box<dyn DynTokenizer + Send + Sync> = Box::new(Whitespace);
+/// assert_eq!(tokenizer.tokenize("a b").unwrap().len(), 2);
+/// ```
+pub trait DynTokenizer {
+    fn tokenize_dyn<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<Box<dyn 'a + Token>>>;
+}
+
+impl<T: Tokenizer> DynTokenizer for T {
+    fn tokenize_dyn<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<Box<dyn 'a + Token>>> {
+        Ok(self
+            .tokenize(text)?
+            .into_iter()
+            .map(|token| Box::new(token) as Box<dyn 'a + Token>)
+            .collect())
+    }
+}
+
+impl Tokenizer for Box<dyn DynTokenizer> {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        self.as_ref().tokenize_dyn(text)
+    }
+}
+
+impl Tokenizer for Box<dyn DynTokenizer + Send + Sync> {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        self.as_ref().tokenize_dyn(text)
+    }
+}
+
+impl<T: Token + ?Sized> Token for Box<T> {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        self.as_mut().fetch()
+    }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        self.as_ref().metadata()
+    }
+}
+
+/// The source of a token or a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenOrigin {
+    /// The word was found in the system dictionary.
+    System,
+    /// The word was found in the user dictionary.
+    User,
+    /// The word was generated by the unknown word model.
+    Unknown,
+    /// The node was created by jpreprocess during preprocessing (e.g. digit conversion).
+    Synthesized,
+}
+
+/// Costs of a token on the best path of the tokenizer lattice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenCost {
+    pub left_context_id: u16,
+    pub right_context_id: u16,
+    pub word_cost: i16,
+    /// Accumulated cost of the best path from the beginning of the sentence
+    /// up to and including this token, if the tokenizer exposes it.
+    ///
+    /// Only the Vibrato tokenizer exposes it; the lindera based tokenizers do not support it.
+    pub path_cost: Option<i32>,
+}
+
+/// Analysis information attached to a token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub origin: TokenOrigin,
+    /// Word id in the dictionary specified by `origin`.
+    pub word_id: Option<u32>,
+    pub cost: Option<TokenCost>,
+}
+
+impl TokenMetadata {
+    pub fn synthesized() -> Self {
+        Self {
+            origin: TokenOrigin::Synthesized,
+            word_id: None,
+            cost: None,
+        }
+    }
 }
 
 impl Token for (String, WordEntry) {
@@ -18,7 +123,14 @@ impl Token for (String, WordEntry) {
 #[cfg(feature = "lindera")]
 impl Tokenizer for lindera::tokenizer::Tokenizer {
     fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
-        Ok(self.tokenize(text)?)
+        Ok(self
+            .tokenize(text)?
+            .into_iter()
+            .map(|token| LinderaToken {
+                source: LinderaTokenSource::new(&token),
+                token,
+            })
+            .collect::<Vec<_>>())
     }
 }
 
@@ -39,7 +151,98 @@ impl Token for lindera::token::Token<'_> {
     }
 }
 
-/// Vibrato support is experimental and may be removed or changed in the future. Use with caution.
+#[cfg(feature = "lindera")]
+struct LinderaToken<'a> {
+    token: lindera::token::Token<'a>,
+    source: LinderaTokenSource<'a>,
+}
+
+#[cfg(feature = "lindera")]
+impl Token for LinderaToken<'_> {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        self.token.fetch()
+    }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        Some(self.source.metadata())
+    }
+}
+
+/// The surface, the dictionaries and the word id of a token returned by lindera,
+/// from which its [`TokenMetadata`] is read on demand.
+#[cfg(feature = "lindera")]
+#[derive(Clone)]
+pub struct LinderaTokenSource<'a> {
+    surface: std::borrow::Cow<'a, str>,
+    dictionary: &'a lindera::dictionary::Dictionary,
+    user_dictionary: Option<&'a lindera::dictionary::UserDictionary>,
+    word_id: lindera::dictionary::WordId,
+}
+
+#[cfg(feature = "lindera")]
+impl<'a> LinderaTokenSource<'a> {
+    pub fn new(token: &lindera::token::Token<'a>) -> Self {
+        Self {
+            surface: token.surface.clone(),
+            dictionary: token.dictionary,
+            user_dictionary: token.user_dictionary,
+            word_id: token.word_id,
+        }
+    }
+
+    /// Reads the metadata of the token.
+    ///
+    /// The costs of system and user words are read from the dictionary entry found by
+    /// an exact-match lookup of the surface, so call this only for the tokens which need it.
+    ///
+    /// The path cost is not supported and is always `None`, as lindera does not expose its lattice.
+    pub fn metadata(&self) -> TokenMetadata {
+        let word_id = self.word_id;
+        let (origin, entry) = if word_id.is_unknown() {
+            (
+                TokenOrigin::Unknown,
+                self.dictionary
+                    .unknown_dictionary
+                    .costs
+                    .get(word_id.id as usize)
+                    .copied(),
+            )
+        } else if word_id.is_system() {
+            (
+                TokenOrigin::System,
+                self.dictionary
+                    .prefix_dictionary
+                    .find_surface_iter(&self.surface)
+                    .find(|entry| entry.word_id.id == word_id.id),
+            )
+        } else {
+            (
+                TokenOrigin::User,
+                self.user_dictionary.and_then(|user| {
+                    user.dict
+                        .find_surface_iter(&self.surface)
+                        .find(|entry| entry.word_id.id == word_id.id)
+                }),
+            )
+        };
+
+        TokenMetadata {
+            origin,
+            word_id: Some(word_id.id),
+            cost: entry.map(|entry| TokenCost {
+                left_context_id: entry.left_id,
+                right_context_id: entry.right_id,
+                word_cost: entry.word_cost,
+                path_cost: None,
+            }),
+        }
+    }
+}
+
+/// Tokenizes with a Vibrato dictionary.
+///
+/// The dictionary is expected to be built by `dict_tools build vibrato`,
+/// whose features are the twelve jpreprocess detail fields in CSV format.
+/// Unknown words are treated the same way as in the lindera tokenizer.
 #[cfg(feature = "vibrato")]
 impl Tokenizer for vibrato::tokenizer::Tokenizer {
     fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
@@ -50,13 +253,116 @@ impl Tokenizer for vibrato::tokenizer::Tokenizer {
         worker
             .token_iter()
             .map(|token| {
-                let features = token.feature();
-                let mut details = features.split(',').collect::<Vec<_>>();
-                details.resize(12, "*");
-                let entry = WordEntry::load(&details)?;
+                let word_idx = token.word_idx();
+                let (origin, entry) = match token.lex_type() {
+                    vibrato::dictionary::LexType::System => {
+                        (TokenOrigin::System, vibrato_word_entry(token.feature())?)
+                    }
+                    vibrato::dictionary::LexType::User => {
+                        (TokenOrigin::User, vibrato_word_entry(token.feature())?)
+                    }
+                    vibrato::dictionary::LexType::Unknown => {
+                        (TokenOrigin::Unknown, WordEntry::default())
+                    }
+                };
 
-                Ok((token.surface().to_string(), entry))
+                Ok(VibratoToken {
+                    surface: token.surface().to_string(),
+                    entry,
+                    metadata: TokenMetadata {
+                        origin,
+                        word_id: Some(word_idx.word_id),
+                        cost: Some(TokenCost {
+                            left_context_id: token.left_id(),
+                            right_context_id: token.right_id(),
+                            word_cost: token.word_cost(),
+                            path_cost: Some(token.total_cost()),
+                        }),
+                    },
+                })
             })
             .collect()
     }
 }
+
+#[cfg(feature = "vibrato")]
+struct VibratoToken {
+    surface: String,
+    entry: WordEntry,
+    metadata: TokenMetadata,
+}
+
+#[cfg(feature = "vibrato")]
+impl Token for VibratoToken {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        Ok((&self.surface, self.entry.clone()))
+    }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        Some(self.metadata)
+    }
+}
+
+/// Parses the feature string of a Vibrato lexicon entry.
+///
+/// Fields may be quoted, as the lexicon is written by a CSV writer.
+/// Missing fields are filled with `*`, as a user lexicon may have only the leading fields.
+#[cfg(feature = "vibrato")]
+fn vibrato_word_entry(feature: &str) -> JPreprocessResult<WordEntry> {
+    let record = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(feature.as_bytes())
+        .into_records()
+        .next()
+        .transpose()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?
+        .unwrap_or_default();
+
+    let mut details = record.iter().collect::<Vec<_>>();
+    details.resize(12, "*");
+    WordEntry::load(&details)
+}
+
+#[cfg(all(test, feature = "vibrato"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vibrato_feature() {
+        let entry =
+            vibrato_word_entry("名詞,一般,*,*,*,*,日本語,ニホンゴ,ニホンゴ,0/4,C2,-1").unwrap();
+        assert_eq!(
+            entry,
+            WordEntry::load(&[
+                "名詞",
+                "一般",
+                "*",
+                "*",
+                "*",
+                "*",
+                "日本語",
+                "ニホンゴ",
+                "ニホンゴ",
+                "0/4",
+                "C2",
+                "-1"
+            ])
+            .unwrap()
+        );
+
+        let quoted = vibrato_word_entry("記号,一般,*,*,*,*,\"，\",、,、,0/0,*,*").unwrap();
+        let WordEntry::Single(details) = quoted else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.read.as_deref(), Some("、"));
+
+        let short = vibrato_word_entry("名詞,一般").unwrap();
+        assert_eq!(
+            short,
+            WordEntry::load(&[
+                "名詞", "一般", "*", "*", "*", "*", "*", "*", "*", "*", "*", "*"
+            ])
+            .unwrap()
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use jpreprocess_core::JPreprocessResult;
 use jpreprocess_dictionary::{
-    dictionary::word_encoding::JPreprocessDictionaryWordEncoding, word_data::get_word_data,
+    dictionary::word_encoding::WordFormat,
+    word_data::{get_identifier, get_word_data},
 };
 use lindera::dictionary::{Dictionary, UserDictionary};
 use lindera_dictionary::dictionary::UNK;
@@ -20,10 +22,12 @@ impl QueryDict {
         }
     }
 
+    /// `format` is the word format of the dictionary, read from its identifier.
     pub fn get_as_jpreprocess(
         &self,
         word_id: u32,
-    ) -> Option<jpreprocess_core::word_entry::WordEntry> {
+        format: WordFormat,
+    ) -> Option<JPreprocessResult<jpreprocess_core::word_entry::WordEntry>> {
         let word_bin = match self {
             Self::System(dict) => get_word_data(
                 &dict.prefix_dictionary.words_idx_data,
@@ -37,7 +41,7 @@ impl QueryDict {
             ),
         };
 
-        JPreprocessDictionaryWordEncoding::deserialize(word_bin?).ok()
+        word_bin.map(|data| Ok(format.deserialize(data)?))
     }
     pub fn get_as_lindera(&self, word_id: u32) -> Option<Vec<&str>> {
         let result = match self {
@@ -54,7 +58,7 @@ impl QueryDict {
 }
 
 fn get_dict_preamble<'a>(idx: &[u8], data: &'a [u8]) -> Option<&'a str> {
-    match std::str::from_utf8(get_word_data(idx, data, None)?) {
+    match std::str::from_utf8(get_identifier(idx, data)?) {
         Ok("") => None,
         Ok(s) => Some(s),
         Err(e) => {
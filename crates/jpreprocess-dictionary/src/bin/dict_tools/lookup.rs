@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+
+use jpreprocess_dictionary::dictionary::export::{lookup_prefix_dictionary, ExportedWord};
+use lindera::{
+    dictionary::{Dictionary, UserDictionary},
+    mode::Mode,
+    segmenter::Segmenter,
+};
+use lindera_dictionary::{error::LinderaErrorKind, viterbi::LexType, LinderaResult};
+
+/// Prints the words of the system and user dictionaries matching `text`.
+pub fn lookup(
+    dictionary: &Dictionary,
+    user_dictionary: Option<&UserDictionary>,
+    text: &str,
+    common_prefix: bool,
+) -> LinderaResult<()> {
+    let mut found = false;
+    for (source, dict) in std::iter::once(("system", &dictionary.prefix_dictionary))
+        .chain(user_dictionary.map(|user_dict| ("user", &user_dict.dict)))
+    {
+        for word in lookup_prefix_dictionary(dict, text, common_prefix)? {
+            println!("{}", describe(source, &word));
+            found = true;
+        }
+    }
+    if !found {
+        println!("No words found.");
+    }
+    Ok(())
+}
+
+/// Segments `text` and prints each token with the dictionary it comes from.
+pub fn segment(
+    dictionary: Dictionary,
+    user_dictionary: Option<UserDictionary>,
+    text: &str,
+) -> LinderaResult<()> {
+    let segmenter = Segmenter::new(Mode::Normal, dictionary, user_dictionary);
+
+    for token in segmenter.segment(Cow::Borrowed(text))? {
+        let (source, dict) = match token.word_id.lex_type {
+            LexType::System => ("system", &token.dictionary.prefix_dictionary),
+            LexType::User => match token.user_dictionary {
+                Some(user_dict) => ("user", &user_dict.dict),
+                None => {
+                    return Err(LinderaErrorKind::Dictionary.with_error(anyhow::anyhow!(
+                        "{} is a user word, but no user dictionary is loaded",
+                        token.surface
+                    )))
+                }
+            },
+            LexType::Unknown => {
+                println!("{}\tunknown", token.surface);
+                continue;
+            }
+        };
+
+        let word = lookup_prefix_dictionary(dict, &token.surface, false)?
+            .into_iter()
+            .find(|word| word.word_id == token.word_id.id);
+        match word {
+            Some(word) => println!("{}", describe(source, &word)),
+            None => println!("{}\t{} id={}", token.surface, source, token.word_id.id),
+        }
+    }
+    Ok(())
+}
+
+fn describe(source: &str, word: &ExportedWord) -> String {
+    let details = match word.details.as_slice() {
+        [pos @ .., _ctype, _cform, _orig, read, pron, acc_morasize, _chain_rule, _chain_flag]
+            if pos.len() == 4 =>
+        {
+            format!(
+                "pos={} read={} pron={} accent={}",
+                pos.join(","),
+                read,
+                pron,
+                acc_morasize
+            )
+        }
+        details => details.join(","),
+    };
+    format!(
+        "{}\t{} id={} left={} right={} cost={}\t{}",
+        word.surface,
+        source,
+        word.word_id,
+        word.left_context_id,
+        word.right_context_id,
+        word.word_cost,
+        details
+    )
+}
@@ -1,13 +1,34 @@
-use std::{error::Error, path::PathBuf};
+use std::{
+    error::Error,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use clap::{Parser, Subcommand, ValueEnum};
-use jpreprocess_dictionary::dictionary::to_dict::JPreprocessDictionaryBuilder;
+use jpreprocess_dictionary::{
+    dictionary::{
+        archive::{archive_dictionary_dir, archive_user_dictionary, ArchiveCompression},
+        convert::{convert_dictionary, convert_user_dictionary, WordEncoding},
+        cost::WordCostEstimator,
+        diff::{diff_words, WordDiff},
+        export::{export_prefix_dictionary, write_csv, ExportedWord},
+        subset::subset_dictionary_source,
+        to_dict::{JPreprocessDictionaryBuilder, SourceSchema, UserRowFormat},
+        user::{preset_contexts, PosPreset, UserDictionaryEditor},
+        word_encoding::WordFormat,
+    },
+    tokenizer::estimate::DictionarySchema,
+};
 use lindera::dictionary::{load_fs_dictionary, load_user_dictionary_from_bin};
-use lindera_dictionary::{builder::DictionaryBuilder, dictionary::metadata::Metadata};
+use lindera_dictionary::{
+    builder::{user_dictionary::build_user_dictionary, DictionaryBuilder},
+    dictionary::metadata::Metadata,
+};
 
 use crate::dict_query::QueryDict;
 
 mod dict_query;
+mod lookup;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -26,7 +47,23 @@ enum Commands {
 
         input: PathBuf,
     },
-    /// Build a dictionary for lindera or jpreprocess
+    /// Look up words by surface, or show how a sentence is segmented
+    Lookup {
+        /// Also list the words whose surface is a prefix of the text
+        #[arg(short, long)]
+        prefix: bool,
+        /// Segment the text and show the dictionary of each token
+        #[arg(short, long, conflicts_with = "prefix")]
+        segment: bool,
+        /// The user dictionary (`.bin`) to use along with the system dictionary
+        #[arg(short, long)]
+        user: Option<PathBuf>,
+
+        /// The system dictionary directory
+        input: PathBuf,
+        text: String,
+    },
+    /// Build a dictionary for lindera, jpreprocess or vibrato
     Build {
         /// Build user dictionary
         #[arg(short, long)]
@@ -34,17 +71,178 @@ enum Commands {
         /// The serializer to be used
         ///
         /// NOTE: `lindera` doesn't reverse `dict.vals`, but `jpreprocess` does (from v0.14.0), causing segmentation differences.
-        /// To align them, build both from the same input and manually copy `dict.vals` from one to the other.
+        /// To align them, build one of them and use the `convert` command to get the other.
         serializer: Serializer,
         /// The path to the metadata file
         #[arg(short, long)]
         metadata: Option<PathBuf>,
+        /// The column layout of the system dictionary source (jpreprocess and vibrato only)
+        #[arg(short, long, value_enum, default_value_t = Schema::NaistJdic)]
+        schema: Schema,
+        /// The input is a compiled MeCab dictionary (jpreprocess only)
+        ///
+        /// For system dictionary, the input is a directory containing `sys.dic`, `unk.dic`, `matrix.bin` and `char.bin`
+        /// (e.g. `open_jtalk_dic_utf_8`). For user dictionary, the input is a `.dic` file built with `mecab-dict-index -u`.
+        #[arg(long)]
+        mecab: bool,
+        /// Parse the user dictionary rows of 3 or 4 fields as `surface,pronunciation,accent[,POS]`
+        #[arg(long, requires = "system")]
+        simplified: bool,
+        /// The system dictionary directory to take the context ids and cost of simplified rows from
+        #[arg(long)]
+        system: Option<PathBuf>,
 
         input: PathBuf,
         /// The directory(system dictionary) or file(user dictionary) to put the dictionary.
         /// For user dictionary, the parent directory of the output file should not exist.
         output: PathBuf,
     },
+    /// Check every row of a user dictionary source and report all the problems
+    Validate {
+        /// The path to the metadata file
+        #[arg(short, long)]
+        metadata: Option<PathBuf>,
+        /// The column layout of rows with context ids and cost
+        #[arg(short, long, value_enum, default_value_t = Schema::NaistJdic)]
+        schema: Schema,
+        /// Parse the rows of 3 or 4 fields as `surface,pronunciation,accent[,POS]`
+        #[arg(long, requires = "system")]
+        simplified: bool,
+        /// The system dictionary directory to take the context ids and cost of simplified rows from
+        #[arg(long)]
+        system: Option<PathBuf>,
+
+        input: PathBuf,
+    },
+    /// Compare two dictionaries and print the added, removed and changed words
+    ///
+    /// Each input is a system dictionary directory, a `.bin` user dictionary, or a user dictionary source CSV.
+    Diff {
+        /// The path to the metadata file for CSV inputs
+        #[arg(short, long)]
+        metadata: Option<PathBuf>,
+
+        old: PathBuf,
+        new: PathBuf,
+    },
+    /// Convert a compiled dictionary between lindera and jpreprocess word encodings
+    ///
+    /// Only the word details are re-encoded, so the segmentation stays identical.
+    Convert {
+        /// The schema of the word details of lindera dictionaries
+        #[arg(short, long, value_enum, default_value_t = DetailsSchema::NaistJdic)]
+        schema: DetailsSchema,
+        /// The word encoding to convert to
+        encoding: Encoding,
+
+        /// The directory(system dictionary) or `.bin` file(user dictionary) to convert
+        input: PathBuf,
+        /// The directory(system dictionary) or file(user dictionary) to put the converted dictionary
+        output: PathBuf,
+    },
+    /// Export a lindera/jpreprocess dictionary as CSV
+    ///
+    /// The rows are ordered by word id, and unknown words are not exported.
+    /// The dictionaries built by lindera or by JPreprocess 0.15 do not record the surfaces, and cannot be exported.
+    Export {
+        /// The directory(system dictionary) or `.bin` file(user dictionary) to export
+        input: PathBuf,
+        /// The CSV file to write
+        output: PathBuf,
+    },
+    /// Estimate the costs of user dictionary words with the connection matrix of a system dictionary
+    ///
+    /// Each cost is set so that the word is chosen for its surface, alone and in the context sentences.
+    Cost {
+        /// A file of context sentences, one per line
+        ///
+        /// The sentences segmented differently with the estimated costs are reported.
+        #[arg(short, long)]
+        contexts: Option<PathBuf>,
+        /// How much lower the cost is than the highest one choosing the word
+        #[arg(short, long, default_value_t = 100)]
+        margin: i16,
+        /// The `.bin` or `.csv` file to write the user dictionary with the estimated costs
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// The system dictionary directory
+        system: PathBuf,
+        /// The user dictionary, `.bin` or source CSV
+        user: PathBuf,
+    },
+    /// Pack a compiled dictionary into a single archive file
+    ///
+    /// The archive can be loaded with `SystemDictionaryConfig::Bytes` or `UserDictionaryConfig::Bytes`.
+    Archive {
+        /// Compress the archive with deflate
+        #[arg(short, long)]
+        compress: bool,
+
+        /// The directory(system dictionary) or `.bin` file(user dictionary) to pack
+        input: PathBuf,
+        /// The archive file to write
+        output: PathBuf,
+    },
+    /// Cut a dictionary source down to the words used in segmenting a corpus
+    ///
+    /// The unknown word definitions are kept, and the context ids are renumbered.
+    Subset {
+        /// The path to the metadata file
+        #[arg(short, long)]
+        metadata: Option<PathBuf>,
+        /// Also build the subset into this directory as a jpreprocess dictionary
+        #[arg(short, long)]
+        build: Option<PathBuf>,
+
+        /// The dictionary source directory
+        src: PathBuf,
+        /// The corpus, one sentence per line
+        corpus: PathBuf,
+        /// The directory to write the subset source
+        output: PathBuf,
+    },
+    /// Add, remove or list the words of a user dictionary
+    User {
+        /// The source CSV kept along with the user dictionary
+        ///
+        /// If given, the CSV is read instead of the user dictionary, and both are rewritten.
+        #[arg(short, long)]
+        csv: Option<PathBuf>,
+        /// The system dictionary directory to take the context ids and cost of new words from
+        ///
+        /// Required to add words.
+        #[arg(short, long)]
+        system: Option<PathBuf>,
+        /// The user dictionary (`.bin`), created if it does not exist
+        dict: PathBuf,
+
+        #[command(subcommand)]
+        action: UserAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UserAction {
+    /// Add a word, replacing the word with the same surface and POS
+    Add {
+        /// The POS of the word: 固有名詞, 一般名詞 or サ変
+        #[arg(short, long, default_value = "固有名詞")]
+        pos: PosPreset,
+        /// The word cost, instead of the one computed from the POS
+        #[arg(short, long, allow_hyphen_values = true)]
+        cost: Option<i16>,
+
+        surface: String,
+        /// The pronunciation in katakana
+        pron: String,
+        /// The accent nucleus, estimated from the POS if omitted
+        accent: Option<usize>,
+    },
+    /// Remove all the words with the surface
+    Remove { surface: String },
+    /// List the words
+    List,
 }
 
 #[derive(Clone, ValueEnum, Debug)]
@@ -53,6 +251,64 @@ enum Serializer {
     Lindera,
     /// Build jpreprocess dictionary
     Jpreprocess,
+    /// Build vibrato dictionary with jpreprocess word details
+    ///
+    /// The output is a single dictionary file for system dictionary, and a lexicon CSV file for user dictionary.
+    Vibrato,
+}
+
+#[derive(Clone, ValueEnum, Debug)]
+enum Schema {
+    /// naist-jdic with the jpreprocess accent fields, laid out by the metadata
+    NaistJdic,
+    /// `lex.csv` of UniDic (unidic-cwj and unidic-csj 3.x)
+    Unidic,
+}
+
+#[derive(Clone, ValueEnum, Debug)]
+enum Encoding {
+    /// Lindera word encoding
+    Lindera,
+    /// JPreprocess word encoding
+    Jpreprocess,
+}
+
+impl From<Encoding> for WordEncoding {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Lindera => WordEncoding::Lindera,
+            Encoding::Jpreprocess => WordEncoding::JPreprocess,
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum, Debug)]
+enum DetailsSchema {
+    /// naist-jdic with the jpreprocess accent fields
+    NaistJdic,
+    /// Plain IPADIC, with the accent fields estimated
+    Ipadic,
+    /// Plain UniDic, with the accent fields estimated
+    Unidic,
+}
+
+impl From<DetailsSchema> for DictionarySchema {
+    fn from(schema: DetailsSchema) -> Self {
+        match schema {
+            DetailsSchema::NaistJdic => DictionarySchema::NaistJdic,
+            DetailsSchema::Ipadic => DictionarySchema::Ipadic,
+            DetailsSchema::Unidic => DictionarySchema::Unidic,
+        }
+    }
+}
+
+impl From<Schema> for SourceSchema {
+    fn from(schema: Schema) -> Self {
+        match schema {
+            Schema::NaistJdic => SourceSchema::NaistJdic,
+            Schema::Unidic => SourceSchema::Unidic,
+        }
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -83,9 +339,17 @@ fn main() -> Result<(), Box<dyn Error>> {
                     QueryDict::User(dict)
                 };
 
+                let mut format = WordFormat::default();
                 let serializer = if let Some(identifier) = dict.identifier() {
                     println!("Dictionary identifier: {}", identifier);
                     if identifier.starts_with("jpreprocess") {
+                        match WordFormat::from_identifier(identifier) {
+                            Ok(found) => {
+                                format = found;
+                                println!("Word format version: {}", format.version());
+                            }
+                            Err(err) => eprintln!("{err}"),
+                        }
                         Serializer::Jpreprocess
                     } else {
                         Serializer::Lindera
@@ -106,21 +370,45 @@ fn main() -> Result<(), Box<dyn Error>> {
                                 println!("{}", detail);
                             }
                         }
-                        Serializer::Jpreprocess => {
-                            let Some(word_details) = dict.get_as_jpreprocess(word_id) else {
+                        Serializer::Jpreprocess | Serializer::Vibrato => {
+                            let Some(word_details) = dict.get_as_jpreprocess(word_id, format)
+                            else {
                                 eprintln!("Word not found");
                                 std::process::exit(-1);
                             };
+                            let word_details = word_details?;
                             println!("{}", word_details.to_str_vec("".to_owned()).join(","));
                         }
                     }
                 }
             }
         }
+        Commands::Lookup {
+            prefix,
+            segment,
+            user,
+            input,
+            text,
+        } => {
+            let dict = load_fs_dictionary(&input)?;
+            let user_dict = user
+                .map(|user| load_user_dictionary_from_bin(&user))
+                .transpose()?;
+
+            if segment {
+                lookup::segment(dict, user_dict, &text)?;
+            } else {
+                lookup::lookup(&dict, user_dict.as_ref(), &text, prefix)?;
+            }
+        }
         Commands::Build {
             user,
             serializer: serializer_config,
             metadata: metadata_path,
+            schema,
+            mecab,
+            simplified,
+            system,
             input,
             output,
         } => {
@@ -134,13 +422,22 @@ fn main() -> Result<(), Box<dyn Error>> {
                         println!("Using default lindera metadata.");
                         Metadata::default()
                     }
-                    Serializer::Jpreprocess => {
+                    Serializer::Jpreprocess | Serializer::Vibrato => {
                         println!("Using default jpreprocess metadata.");
                         JPreprocessDictionaryBuilder::default_metadata()
                     }
                 }
             };
 
+            if system.is_some() && !user {
+                eprintln!("`--simplified` and `--system` are only used for user dictionaries.");
+                std::process::exit(-1);
+            }
+            if mecab && !matches!(serializer_config, Serializer::Jpreprocess) {
+                eprintln!("`--mecab` is only supported by the jpreprocess serializer.");
+                std::process::exit(-1);
+            }
+
             println!("Building dictionary...");
             match serializer_config {
                 Serializer::Lindera => {
@@ -153,18 +450,350 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
                 }
                 Serializer::Jpreprocess => {
-                    let builder = JPreprocessDictionaryBuilder::new(metadata);
+                    let builder = user_rows(
+                        JPreprocessDictionaryBuilder::new(metadata)
+                            .with_source_schema(schema.into()),
+                        simplified,
+                        system.as_deref(),
+                    )?;
+
+                    match (user, mecab) {
+                        (true, true) => {
+                            builder.build_user_dictionary_from_mecab(&input, &output)?
+                        }
+                        (true, false) => builder.build_user_dictionary(&input, &output)?,
+                        (false, true) => builder.build_dictionary_from_mecab(&input, &output)?,
+                        (false, false) => builder.build_dictionary(&input, &output)?,
+                    }
+                }
+                Serializer::Vibrato => {
+                    let builder = user_rows(
+                        JPreprocessDictionaryBuilder::new(metadata)
+                            .with_source_schema(schema.into()),
+                        simplified,
+                        system.as_deref(),
+                    )?;
 
                     if user {
-                        builder.build_user_dictionary(&input, &output)?;
+                        builder.build_vibrato_user_lexicon(&input, &output)?;
                     } else {
-                        builder.build_dictionary(&input, &output)?;
+                        builder.build_vibrato_dictionary(&input, &output)?;
                     }
                 }
             }
             println!("done.");
         }
+        Commands::Validate {
+            metadata: metadata_path,
+            schema,
+            simplified,
+            system,
+            input,
+        } => {
+            let metadata = match metadata_path {
+                Some(metadata_path) => Metadata::load(&std::fs::read(metadata_path)?)?,
+                None => JPreprocessDictionaryBuilder::default_metadata(),
+            };
+            let builder = user_rows(
+                JPreprocessDictionaryBuilder::new(metadata).with_source_schema(schema.into()),
+                simplified,
+                system.as_deref(),
+            )?;
+
+            let issues = builder.validate_user_dictionary(&input)?;
+            for issue in &issues {
+                println!("{}: {}", input.display(), issue);
+            }
+            if !issues.is_empty() {
+                eprintln!("{} problems found.", issues.len());
+                std::process::exit(1);
+            }
+            println!("No problems found.");
+        }
+        Commands::Diff {
+            metadata: metadata_path,
+            old,
+            new,
+        } => {
+            let metadata = match metadata_path {
+                Some(metadata_path) => Metadata::load(&std::fs::read(metadata_path)?)?,
+                None => JPreprocessDictionaryBuilder::default_metadata(),
+            };
+            let field_names = metadata.dictionary_schema.fields.clone();
+            let builder = JPreprocessDictionaryBuilder::new(metadata);
+
+            let diff = diff_words(&load_words(&old, &builder)?, &load_words(&new, &builder)?);
+            for word_diff in &diff {
+                match word_diff {
+                    WordDiff::Added(word) => println!("+ {}", word.to_record().join(",")),
+                    WordDiff::Removed(word) => println!("- {}", word.to_record().join(",")),
+                    WordDiff::Changed { old, new } => {
+                        let (old_record, new_record) = (old.to_record(), new.to_record());
+                        let changes = word_diff
+                            .changed_fields()
+                            .into_iter()
+                            .map(|i| {
+                                let name = field_names
+                                    .get(i)
+                                    .cloned()
+                                    .unwrap_or_else(|| format!("field {i}"));
+                                let get =
+                                    |record: &[String]| record.get(i).cloned().unwrap_or_default();
+                                format!("{name}: {} -> {}", get(&old_record), get(&new_record))
+                            })
+                            .collect::<Vec<_>>();
+                        println!(
+                            "~ {},{}: {}",
+                            old.surface,
+                            old.details
+                                .iter()
+                                .take(6)
+                                .cloned()
+                                .collect::<Vec<_>>()
+                                .join(","),
+                            changes.join(", ")
+                        );
+                    }
+                }
+            }
+            println!("{} differences.", diff.len());
+        }
+        Commands::Convert {
+            schema,
+            encoding,
+            input,
+            output,
+        } => {
+            if input.is_dir() {
+                convert_dictionary(&input, &output, encoding.into(), schema.into())?;
+            } else {
+                convert_user_dictionary(&input, &output, encoding.into(), schema.into())?;
+            }
+            println!("done.");
+        }
+        Commands::Export { input, output } => {
+            let words = load_words(&input, &JPreprocessDictionaryBuilder::default())?;
+            write_csv(&words, File::create(&output)?)?;
+            println!("Exported {} words.", words.len());
+        }
+        Commands::Cost {
+            contexts,
+            margin,
+            output,
+            system,
+            user,
+        } => {
+            let dictionary = load_fs_dictionary(&system)?;
+            let contexts = match contexts {
+                Some(contexts) => std::fs::read_to_string(contexts)?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                None => vec![],
+            };
+            let estimator = WordCostEstimator::new(&dictionary)
+                .with_contexts(contexts)
+                .with_margin(margin);
+
+            let builder = JPreprocessDictionaryBuilder::default();
+            let mut words = load_words(&user, &builder)?;
+            for word in &mut words {
+                let estimate = estimator.estimate(word)?;
+                match estimate.cost {
+                    Some(cost) => {
+                        println!("{}: {} -> {}", word.surface, word.word_cost, cost);
+                        word.word_cost = cost;
+                    }
+                    None => println!(
+                        "{}: {} (not chosen with any cost, kept)",
+                        word.surface, word.word_cost
+                    ),
+                }
+                for context in estimate.unreachable {
+                    println!("  not chosen in: {context}");
+                }
+            }
+
+            let records = words
+                .iter()
+                .map(ExportedWord::to_record)
+                .collect::<Vec<_>>();
+            let user_dict = builder.build_user_dict_from_data(
+                records
+                    .iter()
+                    .map(|record| record.iter().map(String::as_str).collect())
+                    .collect(),
+            )?;
+            for change in estimator.segmentation_changes(&user_dict) {
+                println!("{}", change.sentence);
+                println!("  - {}", change.before.join(" | "));
+                println!("  + {}", change.after.join(" | "));
+            }
+
+            match output {
+                Some(output) if output.extension().is_some_and(|ext| ext == "csv") => {
+                    write_csv(&words, File::create(&output)?)?
+                }
+                Some(output) => build_user_dictionary(user_dict, &output)?,
+                None => (),
+            }
+        }
+        Commands::Archive {
+            compress,
+            input,
+            output,
+        } => {
+            let compression = if compress {
+                ArchiveCompression::Deflate
+            } else {
+                ArchiveCompression::None
+            };
+            let writer = File::create(&output)?;
+            if input.is_dir() {
+                archive_dictionary_dir(&input, compression, writer)?;
+            } else {
+                archive_user_dictionary(&std::fs::read(&input)?, compression, writer)?;
+            }
+            println!("done.");
+        }
+        Commands::Subset {
+            metadata: metadata_path,
+            build,
+            src,
+            corpus,
+            output,
+        } => {
+            let metadata = match metadata_path {
+                Some(metadata_path) => Metadata::load(&std::fs::read(metadata_path)?)?,
+                None => JPreprocessDictionaryBuilder::default_metadata(),
+            };
+            let builder = JPreprocessDictionaryBuilder::new(metadata);
+            let sentences = std::fs::read_to_string(corpus)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+
+            let subset = subset_dictionary_source(&builder, &src, &sentences, &output)?;
+            println!(
+                "Kept {} words and {} context ids.",
+                subset.words.len(),
+                subset.context_ids
+            );
+            for change in &subset.changes {
+                println!("{}", change.sentence);
+                println!("  - {}", change.before.join(" | "));
+                println!("  + {}", change.after.join(" | "));
+            }
+
+            if let Some(build) = build {
+                builder.build_dictionary(&output, &build)?;
+            }
+            println!("done.");
+        }
+        Commands::User {
+            csv,
+            system,
+            dict,
+            action,
+        } => {
+            let mut editor = UserDictionaryEditor::new(JPreprocessDictionaryBuilder::default());
+            if let Some(system) = system {
+                editor = editor
+                    .with_system_dictionary(&load_fs_dictionary(&system)?.prefix_dictionary)?;
+            }
+            editor = match &csv {
+                Some(csv) if csv.exists() => editor.read_csv(csv)?,
+                _ if dict.exists() => editor.read_bin(&dict)?,
+                _ => editor,
+            };
+
+            match action {
+                UserAction::Add {
+                    pos,
+                    cost,
+                    surface,
+                    pron,
+                    accent,
+                } => {
+                    editor.add(&surface, &pron, accent, pos, cost)?;
+                    println!("Added {surface}.");
+                }
+                UserAction::Remove { surface } => {
+                    let removed = editor.remove(&surface);
+                    if removed == 0 {
+                        eprintln!("{surface} is not in the dictionary.");
+                        std::process::exit(1);
+                    }
+                    println!("Removed {removed} words.");
+                }
+                UserAction::List => {
+                    for word in editor.words() {
+                        println!("{}", word.to_record().join(","));
+                    }
+                    return Ok(());
+                }
+            }
+
+            if editor.words().is_empty() {
+                eprintln!(
+                    "A user dictionary must have at least one word. Delete the file instead."
+                );
+                std::process::exit(1);
+            }
+            editor.write_bin(&dict)?;
+            if let Some(csv) = csv {
+                editor.write_csv(&csv)?;
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Reads the words of a compiled dictionary, or of a user dictionary source CSV.
+fn load_words(
+    input: &Path,
+    builder: &JPreprocessDictionaryBuilder,
+) -> Result<Vec<ExportedWord>, Box<dyn Error>> {
+    let words = if input.is_dir() {
+        let dict = load_fs_dictionary(input)?;
+        export_prefix_dictionary(&dict.prefix_dictionary)?
+    } else {
+        match input.extension().and_then(|s| s.to_str()) {
+            Some("bin") => export_prefix_dictionary(&load_user_dictionary_from_bin(input)?.dict)?,
+            Some("csv") => export_prefix_dictionary(&builder.build_user_dict(input)?.dict)?,
+            _ => {
+                return Err(format!(
+                "{input:?} is not a dictionary directory, a `.bin` user dictionary or a CSV file."
+            )
+                .into())
+            }
+        }
+    };
+    Ok(words)
+}
+
+/// Sets up the parsing of simplified user dictionary rows, taking their context ids and cost
+/// from the system dictionary.
+fn user_rows(
+    builder: JPreprocessDictionaryBuilder,
+    simplified: bool,
+    system: Option<&Path>,
+) -> Result<JPreprocessDictionaryBuilder, Box<dyn Error>> {
+    let builder = if simplified {
+        builder.with_user_row_format(UserRowFormat::Simplified)
+    } else {
+        builder
+    };
+    Ok(match system {
+        Some(system) => {
+            let dict = load_fs_dictionary(system)?;
+            builder.with_preset_contexts(preset_contexts(&dict.prefix_dictionary)?)
+        }
+        None => builder,
+    })
+}
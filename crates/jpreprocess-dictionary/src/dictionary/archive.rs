@@ -0,0 +1,376 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+    path::Path,
+};
+
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
+use lindera_dictionary::{
+    dictionary::{
+        character_definition::CharacterDefinition, connection_cost_matrix::ConnectionCostMatrix,
+        metadata::Metadata, prefix_dictionary::PrefixDictionary,
+        unknown_dictionary::UnknownDictionary, Dictionary, UserDictionary,
+    },
+    error::LinderaErrorKind,
+    util::Data,
+    LinderaResult,
+};
+
+const MAGIC: &[u8; 8] = b"JPDICARC";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// The files of a system dictionary directory stored in an archive.
+pub const SYSTEM_COMPONENTS: [&str; 8] = [
+    "metadata.json",
+    "char_def.bin",
+    "unk.bin",
+    "matrix.mtx",
+    "dict.da",
+    "dict.vals",
+    "dict.wordsidx",
+    "dict.words",
+];
+/// The name of the compiled user dictionary in an archive.
+pub const USER_COMPONENT: &str = "user.bin";
+
+/// The compression of the components in an archive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArchiveCompression {
+    /// The components are stored as is, and can be loaded without copying from static bytes.
+    #[default]
+    None,
+    Deflate,
+}
+
+impl ArchiveCompression {
+    fn from_byte(byte: u8) -> LinderaResult<Self> {
+        match byte {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Deflate),
+            _ => Err(LinderaErrorKind::Deserialize
+                .with_error(anyhow::anyhow!("Unknown archive compression: {byte}"))),
+        }
+    }
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Deflate => 1,
+        }
+    }
+}
+
+/// Whether the bytes start as a dictionary archive.
+pub fn is_archive(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Writes the components with their names.
+pub fn write_archive<W: Write>(
+    components: &[(&str, &[u8])],
+    compression: ArchiveCompression,
+    mut writer: W,
+) -> LinderaResult<()> {
+    let io_error = |err: std::io::Error| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context("Failed to write the dictionary archive")
+    };
+
+    writer.write_all(MAGIC).map_err(io_error)?;
+    writer.write_u8(FORMAT_VERSION).map_err(io_error)?;
+    writer.write_u8(compression.to_byte()).map_err(io_error)?;
+
+    match compression {
+        ArchiveCompression::None => write_components(components, &mut writer).map_err(io_error),
+        ArchiveCompression::Deflate => {
+            let mut encoder = DeflateEncoder::new(writer, flate2::Compression::best());
+            write_components(components, &mut encoder).map_err(io_error)?;
+            encoder.finish().map(|_| ()).map_err(io_error)
+        }
+    }
+}
+
+fn write_components<W: Write>(components: &[(&str, &[u8])], writer: &mut W) -> std::io::Result<()> {
+    writer.write_u32::<LittleEndian>(components.len() as u32)?;
+    for (name, data) in components {
+        writer.write_u32::<LittleEndian>(name.len() as u32)?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_u64::<LittleEndian>(data.len() as u64)?;
+        writer.write_all(data)?;
+    }
+    Ok(())
+}
+
+/// Archives the files of a system dictionary directory.
+pub fn archive_dictionary_dir<W: Write>(
+    input_dir: &Path,
+    compression: ArchiveCompression,
+    writer: W,
+) -> LinderaResult<()> {
+    let files = SYSTEM_COMPONENTS
+        .iter()
+        .map(|name| {
+            let path = input_dir.join(name);
+            std::fs::read(&path).map_err(|err| {
+                LinderaErrorKind::Io
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Failed to read {path:?}"))
+            })
+        })
+        .collect::<LinderaResult<Vec<_>>>()?;
+
+    let components = SYSTEM_COMPONENTS
+        .iter()
+        .zip(&files)
+        .map(|(name, data)| (*name, data.as_slice()))
+        .collect::<Vec<_>>();
+    write_archive(&components, compression, writer)
+}
+
+/// Archives a compiled user dictionary (the content of a `.bin` file).
+pub fn archive_user_dictionary<W: Write>(
+    user_dict_data: &[u8],
+    compression: ArchiveCompression,
+    writer: W,
+) -> LinderaResult<()> {
+    write_archive(&[(USER_COMPONENT, user_dict_data)], compression, writer)
+}
+
+/// A single-file container of the components of a dictionary.
+///
+/// An archive holds the files of a system dictionary directory or a compiled user dictionary,
+/// optionally compressed, so that a dictionary can be embedded with `include_bytes!`,
+/// fetched over the network or stored in a database as one blob.
+pub struct DictionaryArchive {
+    components: Vec<(String, Data)>,
+}
+
+impl DictionaryArchive {
+    /// Reads an archive, decompressing it if needed.
+    ///
+    /// The components of uncompressed static bytes are not copied.
+    /// Otherwise each component is copied once into its own buffer, and `bytes` is dropped.
+    pub fn read(bytes: Cow<'static, [u8]>) -> LinderaResult<Self> {
+        match bytes {
+            Cow::Borrowed(bytes) => {
+                if read_header(&mut &bytes[..])? == ArchiveCompression::None {
+                    let components =
+                        static_components(&bytes[HEADER_LEN..]).ok_or_else(truncated)?;
+                    return Ok(Self { components });
+                }
+                Self::from_reader(bytes)
+            }
+            Cow::Owned(bytes) => Self::from_reader(bytes.as_slice()),
+        }
+    }
+
+    /// Reads an archive from a reader, such as a file, decompressing it if needed.
+    ///
+    /// Each component is read into its own buffer without reading the whole archive first.
+    pub fn from_reader<R: Read>(mut reader: R) -> LinderaResult<Self> {
+        let components = match read_header(&mut reader)? {
+            ArchiveCompression::None => read_components(reader),
+            ArchiveCompression::Deflate => read_components(DeflateDecoder::new(reader)),
+        }
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::UnexpectedEof => truncated(),
+            _ => LinderaErrorKind::Decode
+                .with_error(anyhow::anyhow!(err))
+                .add_context("Failed to read the dictionary archive"),
+        })?;
+        Ok(Self { components })
+    }
+
+    /// The names of the components.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.components.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The data of the component.
+    pub fn get(&self, name: &str) -> LinderaResult<&[u8]> {
+        self.position(name).map(|index| &*self.components[index].1)
+    }
+
+    fn position(&self, name: &str) -> LinderaResult<usize> {
+        self.components
+            .iter()
+            .position(|(component, _)| component == name)
+            .ok_or_else(|| {
+                LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(
+                    "The dictionary archive does not contain {name}"
+                ))
+            })
+    }
+
+    /// Takes the data of the component out of the archive.
+    fn take(&mut self, name: &str) -> LinderaResult<Data> {
+        let index = self.position(name)?;
+        Ok(self.components.swap_remove(index).1)
+    }
+
+    /// Loads the system dictionary in the archive.
+    pub fn system_dictionary(mut self) -> LinderaResult<Dictionary> {
+        Ok(Dictionary {
+            metadata: Metadata::load(self.get("metadata.json")?)?,
+            character_definition: CharacterDefinition::load(self.get("char_def.bin")?)?,
+            unknown_dictionary: UnknownDictionary::load(self.get("unk.bin")?)?,
+            prefix_dictionary: PrefixDictionary::load(
+                self.take("dict.da")?,
+                self.take("dict.vals")?,
+                self.take("dict.wordsidx")?,
+                self.take("dict.words")?,
+                true,
+            )?,
+            connection_cost_matrix: ConnectionCostMatrix::load(self.take("matrix.mtx")?)?,
+        })
+    }
+
+    /// Loads the user dictionary in the archive.
+    pub fn user_dictionary(&self) -> LinderaResult<UserDictionary> {
+        UserDictionary::load(self.get(USER_COMPONENT)?)
+    }
+}
+
+fn truncated() -> lindera_dictionary::error::LinderaError {
+    LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!("The dictionary archive is truncated"))
+}
+
+/// Checks the magic and the version, and reads the compression.
+fn read_header<R: Read>(reader: &mut R) -> LinderaResult<ArchiveCompression> {
+    let mut header = [0; HEADER_LEN];
+    if reader.read_exact(&mut header).is_err() || !is_archive(&header) {
+        return Err(LinderaErrorKind::Deserialize
+            .with_error(anyhow::anyhow!("The data is not a dictionary archive")));
+    }
+    let version = header[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(
+            "Unsupported dictionary archive version: {version} (expected {FORMAT_VERSION})"
+        )));
+    }
+    ArchiveCompression::from_byte(header[MAGIC.len() + 1])
+}
+
+fn read_components<R: Read>(mut reader: R) -> std::io::Result<Vec<(String, Data)>> {
+    let count = reader.read_u32::<LittleEndian>()?;
+    (0..count)
+        .map(|_| {
+            let name_len = reader.read_u32::<LittleEndian>()?;
+            let name = String::from_utf8(read_exact(&mut reader, name_len.into())?)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            let data_len = reader.read_u64::<LittleEndian>()?;
+            let data = read_exact(&mut reader, data_len)?;
+            Ok((name, Data::Vec(data)))
+        })
+        .collect()
+}
+
+/// Reads `len` bytes, growing the buffer as the data arrives rather than trusting `len`.
+fn read_exact<R: Read>(reader: &mut R, len: u64) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.by_ref().take(len).read_to_end(&mut data)?;
+    if data.len() as u64 != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(data)
+}
+
+fn static_components(body: &'static [u8]) -> Option<Vec<(String, Data)>> {
+    let mut offset = 0usize;
+    let mut take = |len: usize| {
+        let range = offset..offset.checked_add(len)?;
+        offset = range.end;
+        body.get(range)
+    };
+
+    let count = LittleEndian::read_u32(take(4)?);
+    (0..count)
+        .map(|_| {
+            let name_len = LittleEndian::read_u32(take(4)?) as usize;
+            let name = String::from_utf8(take(name_len)?.to_vec()).ok()?;
+            let data_len = LittleEndian::read_u64(take(8)?) as usize;
+            Some((name, Data::Static(take(data_len)?)))
+        })
+        .collect()
+}
+
+/// Loads a system dictionary from the bytes of an archive.
+pub fn load_dictionary_from_bytes(bytes: Cow<'static, [u8]>) -> LinderaResult<Dictionary> {
+    DictionaryArchive::read(bytes)?.system_dictionary()
+}
+
+/// Loads a system dictionary from an archive read from a reader, such as a file.
+pub fn load_dictionary_from_reader<R: Read>(reader: R) -> LinderaResult<Dictionary> {
+    DictionaryArchive::from_reader(reader)?.system_dictionary()
+}
+
+/// Loads a user dictionary from the bytes of an archive or a compiled `.bin` file.
+pub fn load_user_dictionary_from_bytes(bytes: &[u8]) -> LinderaResult<UserDictionary> {
+    if is_archive(bytes) {
+        DictionaryArchive::from_reader(bytes)?.user_dictionary()
+    } else {
+        UserDictionary::load(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn system_dictionary() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/data/min-dict");
+        let expected = lindera::dictionary::load_fs_dictionary(&dir).unwrap();
+
+        for compression in [ArchiveCompression::None, ArchiveCompression::Deflate] {
+            let mut bytes = Vec::new();
+            archive_dictionary_dir(&dir, compression, &mut bytes).unwrap();
+
+            let from_reader = load_dictionary_from_reader(bytes.as_slice()).unwrap();
+            let static_bytes: &'static [u8] = Box::leak(bytes.clone().into_boxed_slice());
+            let from_static = load_dictionary_from_bytes(Cow::Borrowed(static_bytes)).unwrap();
+            assert_eq!(
+                matches!(from_static.prefix_dictionary.words_data, Data::Static(_)),
+                compression == ArchiveCompression::None
+            );
+
+            let dictionary = load_dictionary_from_bytes(Cow::Owned(bytes)).unwrap();
+            for dictionary in [dictionary, from_reader, from_static] {
+                assert_eq!(
+                    *dictionary.prefix_dictionary.words_data,
+                    *expected.prefix_dictionary.words_data
+                );
+                assert_eq!(
+                    dictionary.connection_cost_matrix.costs_data,
+                    expected.connection_cost_matrix.costs_data
+                );
+                assert_eq!(dictionary.metadata.name, expected.metadata.name);
+            }
+        }
+    }
+
+    #[test]
+    fn user_dictionary() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/data/user/user.bin");
+        let data = std::fs::read(path).unwrap();
+
+        let mut bytes = Vec::new();
+        archive_user_dictionary(&data, ArchiveCompression::Deflate, &mut bytes).unwrap();
+        let archived = load_user_dictionary_from_bytes(&bytes).unwrap();
+        let plain = load_user_dictionary_from_bytes(&data).unwrap();
+        assert_eq!(*archived.dict.words_data, *plain.dict.words_data);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = Vec::new();
+        write_archive(&[("a", b"data")], ArchiveCompression::None, &mut bytes).unwrap();
+        bytes.pop();
+        assert!(DictionaryArchive::from_reader(bytes.as_slice()).is_err());
+        assert!(DictionaryArchive::read(Cow::Owned(bytes)).is_err());
+    }
+}
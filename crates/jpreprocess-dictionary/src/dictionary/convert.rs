@@ -0,0 +1,213 @@
+use std::{fs, path::Path};
+
+use byteorder::{ByteOrder, LittleEndian};
+use jpreprocess_core::word_line::WordDetailsLine;
+use lindera_dictionary::{
+    builder::user_dictionary::build_user_dictionary,
+    dictionary::{prefix_dictionary::PrefixDictionary, UserDictionary},
+    error::LinderaErrorKind,
+    loader::{prefix_dictionary::PrefixDictionaryLoader, user_dictionary::UserDictionaryLoader},
+    LinderaResult,
+};
+
+use crate::{
+    tokenizer::{estimate::DictionarySchema, identify_dictionary::DictionaryIdent},
+    word_data::{encode_preamble, get_surfaces, get_word_data},
+};
+
+use super::word_encoding::{DictionaryWordEncoding, JPreprocessDictionaryWordEncoding, WordFormat};
+
+/// Encoding of the word details in `dict.words`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordEncoding {
+    /// Fields joined with `\0`, prefixed by the length.
+    Lindera,
+    /// [`JPreprocessDictionaryWordEncoding`].
+    JPreprocess,
+}
+
+impl From<DictionaryIdent> for WordEncoding {
+    fn from(ident: DictionaryIdent) -> Self {
+        match ident {
+            DictionaryIdent::Lindera => Self::Lindera,
+            DictionaryIdent::JPreprocess(_) => Self::JPreprocess,
+        }
+    }
+}
+
+/// Re-encodes `dict.words` and `dict.wordsidx` of a prefix dictionary.
+///
+/// `dict.da` and `dict.vals` are kept as they are, so the segmentation does not change.
+/// The surfaces of the words are kept, if the dictionary records them.
+/// `schema` is the schema of the word details when converting from lindera encoding.
+pub fn convert_prefix_dictionary(
+    dict: &PrefixDictionary,
+    encoding: WordEncoding,
+    schema: DictionarySchema,
+) -> LinderaResult<PrefixDictionary> {
+    let ident = DictionaryIdent::from_idx_data(&dict.words_idx_data, &dict.words_data)
+        .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+    // Only used when converting from jpreprocess encoding.
+    let format = match ident {
+        DictionaryIdent::JPreprocess(format) => format,
+        DictionaryIdent::Lindera => WordFormat::default(),
+    };
+    let source = WordEncoding::from(ident);
+
+    let (words, words_idx) = if source == encoding {
+        (dict.words_data.to_vec(), dict.words_idx_data.to_vec())
+    } else {
+        let identifier = match encoding {
+            WordEncoding::Lindera => "",
+            WordEncoding::JPreprocess => JPreprocessDictionaryWordEncoding::identifier(),
+        };
+        let surfaces = get_surfaces(&dict.words_idx_data, &dict.words_data).unwrap_or_default();
+        let mut words = encode_preamble(identifier, surfaces);
+        let mut words_idx = Vec::with_capacity(dict.words_idx_data.len());
+
+        for word_id in 0..dict.words_idx_data.len() / 4 {
+            let data = get_word_data(&dict.words_idx_data, &dict.words_data, Some(word_id))
+                .ok_or_else(|| {
+                    LinderaErrorKind::Deserialize
+                        .with_error(anyhow::anyhow!("Word {} is out of range", word_id))
+                })?;
+
+            words_idx.extend((words.len() as u32).to_le_bytes());
+            match encoding {
+                WordEncoding::Lindera => {
+                    let entry = format
+                        .deserialize(data)
+                        .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+                    let line = WordDetailsLine::from(&entry);
+                    let joined = line.to_str_vec(line.orig.clone()).join("\0");
+
+                    words.extend((joined.len() as u32).to_le_bytes());
+                    words.extend(joined.as_bytes());
+                }
+                WordEncoding::JPreprocess => {
+                    let details = lindera_details(data).ok_or_else(|| {
+                        LinderaErrorKind::Deserialize
+                            .with_error(anyhow::anyhow!("Invalid word details of word {}", word_id))
+                    })?;
+                    let entry = schema.load_word_entry(&details).map_err(|err| {
+                        LinderaErrorKind::Parse
+                            .with_error(err)
+                            .add_context(format!("Failed to convert word {}", word_id))
+                    })?;
+
+                    words.extend(
+                        JPreprocessDictionaryWordEncoding::serialize(&entry)
+                            .map_err(|err| LinderaErrorKind::Serialize.with_error(err))?,
+                    );
+                }
+            }
+        }
+
+        (words, words_idx)
+    };
+
+    PrefixDictionary::load(
+        dict.da.serialize(),
+        dict.vals_data.to_vec(),
+        words_idx,
+        words,
+        dict.is_system,
+    )
+}
+
+/// Converts a compiled system dictionary into `output_dir`.
+///
+/// Only `dict.words` and `dict.wordsidx` are rewritten, and the other files are copied.
+pub fn convert_dictionary(
+    input_dir: &Path,
+    output_dir: &Path,
+    encoding: WordEncoding,
+    schema: DictionarySchema,
+) -> LinderaResult<()> {
+    let io_err = |err: std::io::Error| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err));
+
+    let dict = PrefixDictionaryLoader::load(input_dir)?;
+    let converted = convert_prefix_dictionary(&dict, encoding, schema)?;
+
+    fs::create_dir_all(output_dir).map_err(io_err)?;
+    for entry in fs::read_dir(input_dir).map_err(io_err)? {
+        let path = entry.map_err(io_err)?.path();
+        if path.is_file() {
+            let file_name = path.file_name().unwrap();
+            if file_name != "dict.words" && file_name != "dict.wordsidx" {
+                fs::copy(&path, output_dir.join(file_name)).map_err(io_err)?;
+            }
+        }
+    }
+    fs::write(output_dir.join("dict.words"), &converted.words_data[..]).map_err(io_err)?;
+    fs::write(
+        output_dir.join("dict.wordsidx"),
+        &converted.words_idx_data[..],
+    )
+    .map_err(io_err)?;
+
+    Ok(())
+}
+
+/// Converts a compiled user dictionary (`.bin`) into `output_file`.
+pub fn convert_user_dictionary(
+    input_file: &Path,
+    output_file: &Path,
+    encoding: WordEncoding,
+    schema: DictionarySchema,
+) -> LinderaResult<()> {
+    let user_dict = UserDictionaryLoader::load_from_bin(input_file)?;
+    let dict = convert_prefix_dictionary(&user_dict.dict, encoding, schema)?;
+    build_user_dictionary(UserDictionary { dict }, output_file)
+}
+
+fn lindera_details(data: &[u8]) -> Option<Vec<&str>> {
+    let len = LittleEndian::read_u32(data.get(..4)?) as usize;
+    std::str::from_utf8(data.get(4..4 + len)?)
+        .ok()
+        .map(|joined| joined.split('\0').collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::{
+        export::export_prefix_dictionary, to_dict::JPreprocessDictionaryBuilder,
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let user_dict = JPreprocessDictionaryBuilder::default()
+            .build_user_dict_from_data(vec![
+                vec!["東京", "トウキョウ", "トーキョー"],
+                vec!["東京都", "トウキョウト", "トーキョート"],
+            ])
+            .unwrap();
+
+        let lindera = convert_prefix_dictionary(
+            &user_dict.dict,
+            WordEncoding::Lindera,
+            DictionarySchema::NaistJdic,
+        )
+        .unwrap();
+        assert_eq!(
+            WordEncoding::from(
+                DictionaryIdent::from_idx_data(&lindera.words_idx_data, &lindera.words_data)
+                    .unwrap()
+            ),
+            WordEncoding::Lindera
+        );
+
+        let jpreprocess = convert_prefix_dictionary(
+            &lindera,
+            WordEncoding::JPreprocess,
+            DictionarySchema::NaistJdic,
+        )
+        .unwrap();
+
+        let original = export_prefix_dictionary(&user_dict.dict).unwrap();
+        assert_eq!(export_prefix_dictionary(&lindera).unwrap(), original);
+        assert_eq!(export_prefix_dictionary(&jpreprocess).unwrap(), original);
+    }
+}
@@ -0,0 +1,301 @@
+use lindera_dictionary::{
+    dictionary::{prefix_dictionary::PrefixDictionary, Dictionary, UserDictionary},
+    error::LinderaErrorKind,
+    mode::Mode,
+    viterbi::{Lattice, LexType},
+    LinderaResult,
+};
+
+use super::{export::ExportedWord, to_dict::JPreprocessDictionaryBuilder};
+
+/// The result of [`WordCostEstimator::estimate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// The highest cost with which the word is chosen in all the contexts, minus the margin.
+    ///
+    /// `None` if the word is not chosen in any context even with the lowest cost.
+    pub cost: Option<i16>,
+    /// The contexts in which the word is not chosen even with the lowest cost.
+    pub unreachable: Vec<String>,
+}
+
+/// A sentence segmented differently with a user dictionary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentationChange {
+    pub sentence: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Estimates the costs of user dictionary words with the connection matrix of a system dictionary.
+///
+/// A word is segmented alone and in each context sentence containing it, and its cost is
+/// set so that it is chosen for its surface while being as high as possible,
+/// which keeps the segmentation of the neighbouring words.
+pub struct WordCostEstimator<'a> {
+    dictionary: &'a Dictionary,
+    contexts: Vec<String>,
+    margin: i16,
+}
+
+impl<'a> WordCostEstimator<'a> {
+    pub fn new(dictionary: &'a Dictionary) -> Self {
+        Self {
+            dictionary,
+            contexts: vec![],
+            margin: 100,
+        }
+    }
+
+    /// Sets the sentences in which the words should be chosen.
+    pub fn with_contexts(self, contexts: Vec<String>) -> Self {
+        Self { contexts, ..self }
+    }
+
+    /// Sets how much lower the cost is than the highest one choosing the word. Defaults to 100.
+    pub fn with_margin(self, margin: i16) -> Self {
+        Self { margin, ..self }
+    }
+
+    /// Estimates the cost of the word.
+    ///
+    /// The cost is found by a binary search over the range of `i16`, which builds a one-word
+    /// user dictionary and segments the contexts at each of its 17 steps.
+    pub fn estimate(&self, word: &ExportedWord) -> LinderaResult<CostEstimate> {
+        let matrix = &self.dictionary.connection_cost_matrix;
+        if word.left_context_id as u32 >= matrix.backward_size
+            || word.right_context_id as u32 >= matrix.forward_size
+        {
+            return Err(LinderaErrorKind::Args.with_error(anyhow::anyhow!(
+                "Context ids of {} ({}, {}) are out of the connection matrix ({}, {})",
+                word.surface,
+                word.left_context_id,
+                word.right_context_id,
+                matrix.backward_size,
+                matrix.forward_size
+            )));
+        }
+
+        let contexts = std::iter::once(word.surface.as_str())
+            .chain(
+                self.contexts
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|context| *context != word.surface && context.contains(&word.surface)),
+            )
+            .collect::<Vec<_>>();
+
+        let (reachable, unreachable): (Vec<_>, Vec<_>) = {
+            let user_dict = single_word_dictionary(word, i16::MIN)?;
+            contexts
+                .into_iter()
+                .partition(|context| self.is_chosen(&user_dict.dict, &word.surface, context))
+        };
+
+        if reachable.is_empty() {
+            return Ok(CostEstimate {
+                cost: None,
+                unreachable: unreachable.into_iter().map(str::to_string).collect(),
+            });
+        }
+
+        // The word is chosen in fewer contexts as the cost gets higher.
+        let (mut chosen, mut not_chosen) = (i16::MIN as i32, i16::MAX as i32 + 1);
+        while not_chosen - chosen > 1 {
+            let cost = (chosen + not_chosen) / 2;
+            let user_dict = single_word_dictionary(word, cost as i16)?;
+            if reachable
+                .iter()
+                .all(|context| self.is_chosen(&user_dict.dict, &word.surface, context))
+            {
+                chosen = cost;
+            } else {
+                not_chosen = cost;
+            }
+        }
+
+        Ok(CostEstimate {
+            cost: Some((chosen as i16).saturating_sub(self.margin)),
+            unreachable: unreachable.into_iter().map(str::to_string).collect(),
+        })
+    }
+
+    /// Segments the context sentences with and without the user dictionary,
+    /// and returns the ones segmented differently.
+    pub fn segmentation_changes(&self, user_dict: &UserDictionary) -> Vec<SegmentationChange> {
+        self.contexts
+            .iter()
+            .filter_map(|sentence| {
+                let surfaces = |tokens: Vec<(usize, usize, LexType)>| {
+                    tokens
+                        .into_iter()
+                        .map(|(start, end, _)| sentence[start..end].to_string())
+                        .collect::<Vec<_>>()
+                };
+                let before = surfaces(self.segment(None, sentence));
+                let after = surfaces(self.segment(Some(&user_dict.dict), sentence));
+                (before != after).then(|| SegmentationChange {
+                    sentence: sentence.clone(),
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the user word is chosen at every occurrence of `surface` in `context`.
+    fn is_chosen(&self, user_dict: &PrefixDictionary, surface: &str, context: &str) -> bool {
+        let tokens = self.segment(Some(user_dict), context);
+        context.match_indices(surface).all(|(start, _)| {
+            tokens.iter().any(|&(token_start, token_end, lex_type)| {
+                token_start == start
+                    && token_end == start + surface.len()
+                    && lex_type == LexType::User
+            })
+        })
+    }
+
+    fn segment(
+        &self,
+        user_dict: Option<&PrefixDictionary>,
+        text: &str,
+    ) -> Vec<(usize, usize, LexType)> {
+        let mut lattice = Lattice::default();
+        lattice.set_text(
+            &self.dictionary.prefix_dictionary,
+            &user_dict,
+            &self.dictionary.character_definition,
+            &self.dictionary.unknown_dictionary,
+            &self.dictionary.connection_cost_matrix,
+            text,
+            &Mode::Normal,
+        );
+
+        let offsets = lattice.tokens_offset();
+        offsets
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, word_id))| {
+                let end = offsets.get(i + 1).map_or(text.len(), |&(end, _)| end);
+                (start, end, word_id.lex_type)
+            })
+            .collect()
+    }
+}
+
+fn single_word_dictionary(word: &ExportedWord, cost: i16) -> LinderaResult<UserDictionary> {
+    let record = ExportedWord {
+        word_cost: cost,
+        ..word.clone()
+    }
+    .to_record();
+    JPreprocessDictionaryBuilder::default()
+        .build_user_dict_from_data(vec![record.iter().map(String::as_str).collect()])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lindera::dictionary::load_fs_dictionary;
+
+    use super::*;
+    use crate::util::TempDir;
+
+    fn word(surface: &str) -> ExportedWord {
+        ExportedWord {
+            word_id: 0,
+            surface: surface.to_string(),
+            left_context_id: 5,
+            right_context_id: 5,
+            word_cost: 0,
+            details: [
+                "名詞",
+                "一般",
+                "*",
+                "*",
+                "*",
+                "*",
+                "*",
+                "オンセイゴウセイ",
+                "オンセイゴーセイ",
+                "5/8",
+                "C1",
+                "-1",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        }
+    }
+
+    #[test]
+    fn estimate() {
+        let dictionary = load_fs_dictionary(
+            &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/data/min-dict"),
+        )
+        .unwrap();
+        let estimator = WordCostEstimator::new(&dictionary).with_contexts(vec![
+            "音声合成を解析します".to_string(),
+            "音声を合成します".to_string(),
+        ]);
+
+        let word = word("音声合成");
+        let estimate = estimator.estimate(&word).unwrap();
+        assert!(estimate.unreachable.is_empty());
+
+        let cost = estimate.cost.unwrap();
+
+        let user_dict = single_word_dictionary(&word, cost).unwrap();
+        assert!(estimator.is_chosen(&user_dict.dict, "音声合成", "音声合成を解析します"));
+        let user_dict = single_word_dictionary(&word, cost + 101).unwrap();
+        assert!(!estimator.is_chosen(&user_dict.dict, "音声合成", "音声合成"));
+
+        let user_dict = single_word_dictionary(&word, cost).unwrap();
+        assert_eq!(
+            estimator.segmentation_changes(&user_dict),
+            [SegmentationChange {
+                sentence: "音声合成を解析します".to_string(),
+                before: ["音声", "合成", "を", "解析", "し", "ます"]
+                    .map(str::to_string)
+                    .to_vec(),
+                after: ["音声合成", "を", "解析", "し", "ます"]
+                    .map(str::to_string)
+                    .to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn never_chosen() {
+        // Connecting to or from context id 1 costs more than any word cost can make up for.
+        let src = TempDir::new().unwrap();
+        for (name, content) in [
+            ("matrix.def", "2 2\n0 0 0\n0 1 32767\n1 0 32767\n1 1 0\n"),
+            ("char.def", "DEFAULT 1 1 0\nSPACE 0 1 0\n\n0x0020 SPACE\n"),
+            (
+                "unk.def",
+                "DEFAULT,0,0,5000,記号,一般,*,*,*,*,*\nSPACE,0,0,5000,記号,空白,*,*,*,*,*\n",
+            ),
+            (
+                "words.csv",
+                "解析,0,0,100,名詞,サ変接続,*,*,*,*,解析,カイセキ,カイセキ,0/4,C2\n",
+            ),
+        ] {
+            std::fs::write(src.path().join(name), content).unwrap();
+        }
+        let dict_dir = TempDir::new().unwrap();
+        JPreprocessDictionaryBuilder::default()
+            .build_dictionary(src.path(), dict_dir.path())
+            .unwrap();
+        let dictionary = load_fs_dictionary(dict_dir.path()).unwrap();
+
+        let word = ExportedWord {
+            left_context_id: 1,
+            right_context_id: 1,
+            ..word("音声")
+        };
+        let estimate = WordCostEstimator::new(&dictionary).estimate(&word).unwrap();
+        assert_eq!(estimate.cost, None);
+        assert_eq!(estimate.unreachable, ["音声"]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use super::export::ExportedWord;
+
+/// Number of detail fields identifying a word along with its surface: POS, CType and CForm.
+const KEY_DETAILS_LEN: usize = 6;
+
+/// A difference between two dictionaries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WordDiff {
+    Added(ExportedWord),
+    Removed(ExportedWord),
+    /// The word has the same surface, POS and conjugation, but different
+    /// context ids, cost or the other details.
+    Changed {
+        old: ExportedWord,
+        new: ExportedWord,
+    },
+}
+
+impl WordDiff {
+    /// Indices of the fields in [`ExportedWord::to_record`] which differ.
+    pub fn changed_fields(&self) -> Vec<usize> {
+        match self {
+            Self::Changed { old, new } => {
+                let (old, new) = (old.to_record(), new.to_record());
+                (0..old.len().max(new.len()))
+                    .filter(|&i| old.get(i) != new.get(i))
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Compares the words of two dictionaries, e.g. from
+/// [`export_prefix_dictionary`](super::export::export_prefix_dictionary).
+///
+/// Words are matched by surface, POS and conjugation, and word ids are ignored.
+/// The result is ordered by surface.
+pub fn diff_words(old: &[ExportedWord], new: &[ExportedWord]) -> Vec<WordDiff> {
+    // The old and new words for each key.
+    type Candidates<'a> = (Vec<&'a ExportedWord>, Vec<&'a ExportedWord>);
+    let mut words: BTreeMap<(&str, &[String]), Candidates> = BTreeMap::new();
+    for word in old {
+        words.entry(key(word)).or_default().0.push(word);
+    }
+    for word in new {
+        words.entry(key(word)).or_default().1.push(word);
+    }
+
+    let mut result = Vec::new();
+    for (_, (old, mut new)) in words {
+        // Identical words are removed first so that the rest are paired in order.
+        let mut changed_old = Vec::new();
+        for word in old {
+            match new.iter().position(|other| same_entry(word, other)) {
+                Some(i) => {
+                    new.remove(i);
+                }
+                None => changed_old.push(word),
+            }
+        }
+
+        let mut new = new.into_iter();
+        for old in changed_old {
+            match new.next() {
+                Some(new) => result.push(WordDiff::Changed {
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                None => result.push(WordDiff::Removed(old.clone())),
+            }
+        }
+        result.extend(new.map(|new| WordDiff::Added(new.clone())));
+    }
+
+    result
+}
+
+fn key(word: &ExportedWord) -> (&str, &[String]) {
+    (
+        &word.surface,
+        &word.details[..word.details.len().min(KEY_DETAILS_LEN)],
+    )
+}
+
+fn same_entry(a: &ExportedWord, b: &ExportedWord) -> bool {
+    a.left_context_id == b.left_context_id
+        && a.right_context_id == b.right_context_id
+        && a.word_cost == b.word_cost
+        && a.details == b.details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word_id: u32, surface: &str, cost: i16, pos: &str, pron: &str) -> ExportedWord {
+        ExportedWord {
+            word_id,
+            surface: surface.to_string(),
+            left_context_id: 1,
+            right_context_id: 1,
+            word_cost: cost,
+            details: [pos, "*", "*", "*", "*", "*", "*", pron, pron, "0/3"]
+                .map(str::to_string)
+                .to_vec(),
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let old = [
+            word(0, "あ", 100, "感動詞", "ア"),
+            word(1, "い", 100, "名詞", "イ"),
+            word(2, "う", 100, "名詞", "ウ"),
+            word(3, "え", 100, "名詞", "エ"),
+        ];
+        let new = [
+            word(0, "あ", 100, "感動詞", "ア"),
+            word(1, "い", 200, "名詞", "イ"),
+            word(2, "え", 100, "名詞", "エ"),
+            word(3, "え", 100, "感動詞", "エ"),
+        ];
+
+        let diff = diff_words(&old, &new);
+        assert_eq!(
+            diff,
+            [
+                WordDiff::Changed {
+                    old: old[1].clone(),
+                    new: new[1].clone(),
+                },
+                WordDiff::Removed(old[2].clone()),
+                WordDiff::Added(new[3].clone()),
+            ]
+        );
+        assert_eq!(diff[0].changed_fields(), [3]);
+    }
+}
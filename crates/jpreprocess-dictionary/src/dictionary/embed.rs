@@ -0,0 +1,181 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use lindera::dictionary::load_fs_dictionary;
+use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
+
+use crate::util::TempDir;
+
+use super::{
+    archive::{archive_dictionary_dir, archive_user_dictionary, ArchiveCompression},
+    to_dict::JPreprocessDictionaryBuilder,
+    user::preset_contexts,
+};
+
+/// Builds a dictionary from its source in a build script, so that it can be embedded in the binary.
+///
+/// The system dictionary is written to `$OUT_DIR/<name>.jpdic`, and the user dictionary,
+/// if any, to `$OUT_DIR/<name>.user.jpdic`.
+/// They are loaded with `jpreprocess::embedded_dictionary!` and `jpreprocess::embedded_user_dictionary!`.
+///
+/// ```rust,no_run
+/// # use std::path::Path;
+/// use jpreprocess_dictionary::dictionary::{
+///     embed::EmbeddedDictionaryBuilder, to_dict::JPreprocessDictionaryBuilder,
+/// };
+///
+/// // build.rs
+/// EmbeddedDictionaryBuilder::new("custom", JPreprocessDictionaryBuilder::default())
+///     .with_user_dictionary("dict/user.csv")
+///     .build(Path::new("dict/src"))
+///     .unwrap();
+/// ```
+pub struct EmbeddedDictionaryBuilder {
+    name: String,
+    builder: JPreprocessDictionaryBuilder,
+    user_dictionary: Option<PathBuf>,
+    compression: ArchiveCompression,
+}
+
+impl EmbeddedDictionaryBuilder {
+    pub fn new(name: &str, builder: JPreprocessDictionaryBuilder) -> Self {
+        Self {
+            name: name.to_string(),
+            builder,
+            user_dictionary: None,
+            compression: ArchiveCompression::None,
+        }
+    }
+
+    /// Sets the user dictionary source CSV.
+    ///
+    /// Simplified rows ([`UserRowFormat::Simplified`](super::to_dict::UserRowFormat::Simplified))
+    /// get the context ids of the built system dictionary.
+    pub fn with_user_dictionary(self, csv: impl Into<PathBuf>) -> Self {
+        Self {
+            user_dictionary: Some(csv.into()),
+            ..self
+        }
+    }
+
+    /// Sets the compression of the archives. Defaults to none, which makes loading faster
+    /// but the binary larger.
+    pub fn with_compression(self, compression: ArchiveCompression) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    /// Builds the dictionaries into `OUT_DIR` of the build script.
+    pub fn build(&self, src_dir: &Path) -> LinderaResult<()> {
+        let out_dir = std::env::var_os("OUT_DIR").ok_or_else(|| {
+            LinderaErrorKind::Args.with_error(anyhow::anyhow!(
+                "OUT_DIR is not set. Call this in a build script."
+            ))
+        })?;
+
+        println!("cargo:rerun-if-changed={}", src_dir.display());
+        if let Some(csv) = &self.user_dictionary {
+            println!("cargo:rerun-if-changed={}", csv.display());
+        }
+
+        self.build_to(src_dir, Path::new(&out_dir))
+    }
+
+    /// Builds the dictionaries into `out_dir`.
+    pub fn build_to(&self, src_dir: &Path, out_dir: &Path) -> LinderaResult<()> {
+        let work_dir = TempDir::new()?;
+        let dict_dir = work_dir.path().join("dict");
+        self.builder.build_dictionary(src_dir, &dict_dir)?;
+
+        archive_dictionary_dir(
+            &dict_dir,
+            self.compression,
+            create(&out_dir.join(format!("{}.jpdic", self.name)))?,
+        )?;
+
+        if let Some(csv) = &self.user_dictionary {
+            let dictionary = load_fs_dictionary(&dict_dir)?;
+            let user_bin = work_dir.path().join("user.bin");
+            self.builder
+                .clone()
+                .with_preset_contexts(preset_contexts(&dictionary.prefix_dictionary)?)
+                .build_user_dictionary(csv, &user_bin)?;
+            let data = std::fs::read(&user_bin).map_err(|err| {
+                LinderaErrorKind::Io
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Failed to read {user_bin:?}"))
+            })?;
+            archive_user_dictionary(
+                &data,
+                self.compression,
+                create(&out_dir.join(format!("{}.user.jpdic", self.name)))?,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn create(path: &Path) -> LinderaResult<File> {
+    File::create(path).map_err(|err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to create {path:?}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, fs};
+
+    use super::*;
+    use crate::dictionary::{
+        archive::{load_dictionary_from_bytes, load_user_dictionary_from_bytes},
+        to_dict::UserRowFormat,
+    };
+
+    #[test]
+    fn build() {
+        let src = TempDir::new().unwrap();
+        for (name, content) in [
+            ("matrix.def", "1 1\n0 0 0\n"),
+            ("char.def", "DEFAULT 0 1 0\nSPACE 0 1 0\n\n0x0020 SPACE\n"),
+            (
+                "unk.def",
+                "DEFAULT,0,0,0,記号,一般,*,*,*,*,*\nSPACE,0,0,0,記号,空白,*,*,*,*,*\n",
+            ),
+            (
+                "words.csv",
+                "音声,0,0,100,名詞,一般,*,*,*,*,音声,オンセイ,オンセー,1/4,C1\n",
+            ),
+        ] {
+            fs::write(src.path().join(name), content).unwrap();
+        }
+
+        let out = TempDir::new().unwrap();
+        let user_csv = out.path().join("user.csv");
+        fs::write(&user_csv, "音声合成,オンセイゴーセイ,5,一般名詞\n").unwrap();
+
+        let builder =
+            JPreprocessDictionaryBuilder::default().with_user_row_format(UserRowFormat::Simplified);
+        EmbeddedDictionaryBuilder::new("custom", builder)
+            .with_user_dictionary(user_csv)
+            .with_compression(ArchiveCompression::Deflate)
+            .build_to(src.path(), out.path())
+            .unwrap();
+
+        let system = fs::read(out.path().join("custom.jpdic")).unwrap();
+        let system = load_dictionary_from_bytes(Cow::Owned(system)).unwrap();
+        assert_eq!(system.prefix_dictionary.find_surface("音声").len(), 1);
+
+        let user = fs::read(out.path().join("custom.user.jpdic")).unwrap();
+        let user = load_user_dictionary_from_bytes(&user).unwrap();
+        let words = user.dict.find_surface("音声合成");
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].left_id, 0);
+    }
+}
@@ -0,0 +1,304 @@
+use std::io::Write;
+
+use byteorder::{ByteOrder, LittleEndian};
+use jpreprocess_core::word_line::WordDetailsLine;
+use lindera_dictionary::{
+    dictionary::prefix_dictionary::PrefixDictionary, error::LinderaErrorKind, viterbi::WordEntry,
+    LinderaResult,
+};
+
+use crate::{
+    tokenizer::identify_dictionary::DictionaryIdent,
+    word_data::{get_surfaces, get_word_data},
+};
+
+/// A word of a compiled dictionary, reconstructed as a source row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportedWord {
+    pub word_id: u32,
+    pub surface: String,
+    pub left_context_id: u16,
+    pub right_context_id: u16,
+    pub word_cost: i16,
+    /// The fields after the cost.
+    ///
+    /// For jpreprocess dictionaries, these are the 12 naist-jdic fields.
+    ///
+    /// The export of jpreprocess dictionaries is lossy: the dictionary does not keep `orig`
+    /// (the base form) of a single word, so it is exported as `*`, and only compound words
+    /// have it. Rebuilding from the export gives the same tokens, but not the same source.
+    pub details: Vec<String>,
+}
+
+impl ExportedWord {
+    /// The CSV row of the word: surface, context ids, cost and details.
+    pub fn to_record(&self) -> Vec<String> {
+        let mut record = vec![
+            self.surface.clone(),
+            self.left_context_id.to_string(),
+            self.right_context_id.to_string(),
+            self.word_cost.to_string(),
+        ];
+        record.extend(self.details.iter().cloned());
+        record
+    }
+}
+
+/// Reconstructs all the words in a compiled dictionary (`dict.vals`, `dict.wordsidx` and `dict.words`),
+/// ordered by word id.
+///
+/// The surfaces are those recorded in `dict.words`, so the dictionaries built by lindera or
+/// by JPreprocess 0.15 cannot be exported; rebuild them from the source.
+/// Both jpreprocess and lindera word encodings are supported.
+/// `orig` is lost for jpreprocess dictionaries; see [`ExportedWord::details`].
+/// Unknown words (`unk.bin`) are not included.
+pub fn export_prefix_dictionary(dict: &PrefixDictionary) -> LinderaResult<Vec<ExportedWord>> {
+    let ident = DictionaryIdent::from_idx_data(&dict.words_idx_data, &dict.words_data)
+        .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+    let surfaces = get_surfaces(&dict.words_idx_data, &dict.words_data).ok_or_else(|| {
+        LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(
+            "The dictionary does not record the surfaces of its words. Rebuild it from the source."
+        ))
+    })?;
+
+    let mut words = dict
+        .vals_data
+        .chunks_exact(WordEntry::SERIALIZED_LEN)
+        .map(|data| {
+            let entry = WordEntry::deserialize(data, dict.is_system);
+            let surface = surfaces.get(entry.word_id.id as usize).ok_or_else(|| {
+                LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(
+                    "No surface is recorded for word {}",
+                    entry.word_id.id
+                ))
+            })?;
+            Ok(ExportedWord {
+                word_id: entry.word_id.id,
+                details: word_details(dict, &ident, entry.word_id.id)?,
+                surface: surface.to_string(),
+                left_context_id: entry.left_id,
+                right_context_id: entry.right_id,
+                word_cost: entry.word_cost,
+            })
+        })
+        .collect::<LinderaResult<Vec<_>>>()?;
+    words.sort_by_key(|word| word.word_id);
+
+    Ok(words)
+}
+
+/// Writes the words as a headerless CSV, the format accepted by the dictionary builders.
+pub fn write_csv<W: Write>(words: &[ExportedWord], writer: W) -> LinderaResult<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(writer);
+    for word in words {
+        writer
+            .write_record(word.to_record())
+            .map_err(|err| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err)))?;
+    }
+    writer
+        .flush()
+        .map_err(|err| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err)))
+}
+
+/// Finds the words whose surface is `text`, or a prefix of `text` if `common_prefix` is set.
+///
+/// The words are ordered by surface length and word id.
+pub fn lookup_prefix_dictionary(
+    dict: &PrefixDictionary,
+    text: &str,
+    common_prefix: bool,
+) -> LinderaResult<Vec<ExportedWord>> {
+    let ident = DictionaryIdent::from_idx_data(&dict.words_idx_data, &dict.words_data)
+        .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+
+    let mut words = dict
+        .prefix(text)
+        .filter(|(end, _)| common_prefix || *end == text.len())
+        .map(|(end, entry)| {
+            Ok(ExportedWord {
+                word_id: entry.word_id.id,
+                details: word_details(dict, &ident, entry.word_id.id)?,
+                surface: text[..end].to_string(),
+                left_context_id: entry.left_id,
+                right_context_id: entry.right_id,
+                word_cost: entry.word_cost,
+            })
+        })
+        .collect::<LinderaResult<Vec<_>>>()?;
+    words.sort_by_key(|word| (word.surface.len(), word.word_id));
+
+    Ok(words)
+}
+
+pub(crate) fn word_details(
+    dict: &PrefixDictionary,
+    ident: &DictionaryIdent,
+    word_id: u32,
+) -> LinderaResult<Vec<String>> {
+    let invalid = || {
+        LinderaErrorKind::Deserialize
+            .with_error(anyhow::anyhow!("Invalid word details of word {}", word_id))
+    };
+
+    let data = get_word_data(
+        &dict.words_idx_data,
+        &dict.words_data,
+        Some(word_id as usize),
+    )
+    .ok_or_else(invalid)?;
+
+    match ident {
+        DictionaryIdent::JPreprocess(format) => {
+            let entry = format
+                .deserialize(data)
+                .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+            let line = WordDetailsLine::from(&entry);
+            Ok(line.to_str_vec(line.orig.clone()).to_vec())
+        }
+        DictionaryIdent::Lindera => {
+            // A u32 length followed by the details joined with `\0`.
+            let len = LittleEndian::read_u32(data.get(..4).ok_or_else(invalid)?) as usize;
+            let joined = data.get(4..4 + len).ok_or_else(invalid)?;
+            joined
+                .split(|&b| b == 0)
+                .map(|detail| String::from_utf8(detail.to_vec()).map_err(|_| invalid()))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dictionary::to_dict::JPreprocessDictionaryBuilder, word_data::get_identifier};
+
+    use super::*;
+
+    #[test]
+    fn without_surfaces() {
+        let user_dict = JPreprocessDictionaryBuilder::default()
+            .build_user_dict_from_data(vec![vec!["東京", "トウキョウ", "トーキョー"]])
+            .unwrap();
+        let dict = &user_dict.dict;
+        assert_eq!(export_prefix_dictionary(dict).unwrap()[0].surface, "東京");
+
+        // A dictionary built by JPreprocess 0.15 has only the identifier before the words.
+        let identifier_len = get_identifier(&dict.words_idx_data, &dict.words_data)
+            .unwrap()
+            .len();
+        let offset = |idx: &[u8]| u32::from_le_bytes(idx.try_into().unwrap()) as usize;
+        let skipped = offset(&dict.words_idx_data[..4]) - identifier_len;
+        let words_idx = dict
+            .words_idx_data
+            .chunks_exact(4)
+            .flat_map(|idx| ((offset(idx) - skipped) as u32).to_le_bytes())
+            .collect::<Vec<_>>();
+        let mut words = dict.words_data[..identifier_len].to_vec();
+        words.extend(&dict.words_data[identifier_len + skipped..]);
+        let legacy = PrefixDictionary::load(
+            dict.da.serialize(),
+            dict.vals_data.to_vec(),
+            words_idx,
+            words,
+            false,
+        )
+        .unwrap();
+
+        assert!(export_prefix_dictionary(&legacy).is_err());
+        assert_eq!(
+            lookup_prefix_dictionary(&legacy, "東京", false).unwrap()[0].details,
+            export_prefix_dictionary(dict).unwrap()[0].details
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let user_dict = JPreprocessDictionaryBuilder::default()
+            .build_user_dict_from_data(vec![
+                vec!["東京", "トウキョウ", "トーキョー"],
+                vec!["東京都", "トウキョウト", "トーキョート"],
+                vec!["京都", "キョウト", "キョート"],
+            ])
+            .unwrap();
+
+        let surfaces = |text, common_prefix| {
+            lookup_prefix_dictionary(&user_dict.dict, text, common_prefix)
+                .unwrap()
+                .into_iter()
+                .map(|word| (word.surface, word.word_id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(surfaces("東京都", false), [("東京都".to_string(), 1)]);
+        assert_eq!(
+            surfaces("東京都庁", true),
+            [("東京".to_string(), 0), ("東京都".to_string(), 1)]
+        );
+        assert!(surfaces("東京都庁", false).is_empty());
+    }
+
+    #[test]
+    fn user_dictionary() {
+        let rows = [
+            [
+                "東京スカイツリー",
+                "1285",
+                "1285",
+                "-3000",
+                "名詞",
+                "固有名詞",
+                "一般",
+                "*",
+                "*",
+                "*",
+                "*",
+                "トウキョウスカイツリー",
+                "トーキョースカイツリー",
+                "5/10",
+                "C1",
+                "-1",
+            ],
+            [
+                "東京",
+                "1293",
+                "1293",
+                "-2000",
+                "名詞",
+                "固有名詞",
+                "地域",
+                "一般",
+                "*",
+                "*",
+                "*",
+                "トウキョウ",
+                "トーキョー",
+                "0/4",
+                "C1",
+                "-1",
+            ],
+        ];
+        let user_dict = JPreprocessDictionaryBuilder::default()
+            .build_user_dict_from_data(rows.iter().map(|row| row.to_vec()).collect())
+            .unwrap();
+
+        let words = export_prefix_dictionary(&user_dict.dict).unwrap();
+        assert_eq!(
+            words.iter().map(|word| word.word_id).collect::<Vec<_>>(),
+            [0, 1]
+        );
+        assert_eq!(
+            words
+                .iter()
+                .map(ExportedWord::to_record)
+                .collect::<Vec<_>>(),
+            rows.map(|row| row.map(str::to_string).to_vec())
+        );
+
+        let mut csv = Vec::new();
+        write_csv(&words, &mut csv).unwrap();
+        assert!(String::from_utf8(csv)
+            .unwrap()
+            .starts_with("東京スカイツリー,1285,1285,-3000,名詞,固有名詞,一般,*,*,*,*,"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::Write;
+
+use byteorder::{ByteOrder, LittleEndian};
+use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
+
+/// Number of code points in `char.bin` (U+0000 to U+FFFE).
+const TABLE_SIZE: usize = 0xffff;
+
+/// Character information of `char.bin`, a bit field of
+/// `type: 18, default_type: 8, length: 4, group: 1, invoke: 1`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CharInfo(u32);
+
+impl CharInfo {
+    fn types(self) -> u32 {
+        self.0 & 0x3ffff
+    }
+    fn default_type(self) -> usize {
+        (self.0 >> 18 & 0xff) as usize
+    }
+    fn length(self) -> u32 {
+        self.0 >> 26 & 0xf
+    }
+    fn group(self) -> u32 {
+        self.0 >> 30 & 1
+    }
+    fn invoke(self) -> u32 {
+        self.0 >> 31
+    }
+}
+
+/// Decodes `char.bin` of a MeCab dictionary and writes it as `char.def`.
+pub fn decode_char_property<W: Write>(data: &[u8], mut writer: W) -> LinderaResult<()> {
+    let invalid =
+        || LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!("Invalid char.bin size"));
+
+    let category_size = LittleEndian::read_u32(data.get(0..4).ok_or_else(invalid)?) as usize;
+    let table_offset = 4 + 32 * category_size;
+    if data.len() != table_offset + 4 * TABLE_SIZE {
+        return Err(invalid());
+    }
+
+    let names = data[4..table_offset]
+        .chunks_exact(32)
+        .map(|name| {
+            let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            String::from_utf8_lossy(&name[..end]).into_owned()
+        })
+        .collect::<Vec<_>>();
+    let table = data[table_offset..]
+        .chunks_exact(4)
+        .map(|info| CharInfo(LittleEndian::read_u32(info)))
+        .collect::<Vec<_>>();
+
+    let write_err = |err: std::io::Error| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err));
+
+    // The parameters of a category are stored in the characters it is the default of.
+    for (id, name) in names.iter().enumerate() {
+        let (invoke, group, length) = table
+            .iter()
+            .find(|info| info.default_type() == id)
+            .map_or((0, 0, 0), |info| {
+                (info.invoke(), info.group(), info.length())
+            });
+        writeln!(writer, "{name} {invoke} {group} {length}").map_err(write_err)?;
+    }
+
+    // Characters without a range belong to DEFAULT.
+    let default = names.iter().position(|name| name == "DEFAULT");
+    let is_default = |info: CharInfo| {
+        default.is_some_and(|id| info.default_type() == id && info.types() == 1 << id)
+    };
+
+    let mut start = 0;
+    while start < TABLE_SIZE {
+        let info = table[start];
+        let end = (start..TABLE_SIZE)
+            .find(|&i| table[i] != info)
+            .unwrap_or(TABLE_SIZE);
+
+        if !is_default(info) {
+            let range = if end - start == 1 {
+                format!("0x{start:04X}")
+            } else {
+                format!("0x{start:04X}..0x{:04X}", end - 1)
+            };
+            let categories = std::iter::once(info.default_type())
+                .chain((0..names.len()).filter(|&id| {
+                    id != info.default_type() && id < 18 && info.types() & 1 << id != 0
+                }))
+                .map(|id| names.get(id).map(String::as_str).ok_or_else(invalid))
+                .collect::<LinderaResult<Vec<_>>>()?;
+            writeln!(writer, "{range} {}", categories.join(" ")).map_err(write_err)?;
+        }
+
+        start = end;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(super) fn encode_char_property(
+    categories: &[(&str, u32, u32, u32)],
+    ranges: &[(u32, u32, &[usize])],
+) -> Vec<u8> {
+    let info = |ids: &[usize]| {
+        let (_, invoke, group, length) = categories[ids[0]];
+        let types = ids.iter().fold(0, |types, id| types | 1 << id);
+        types | (ids[0] as u32) << 18 | length << 26 | group << 30 | invoke << 31
+    };
+
+    let mut table = vec![info(&[0]); TABLE_SIZE];
+    for (low, high, ids) in ranges {
+        for c in *low..=*high {
+            table[c as usize] = info(ids);
+        }
+    }
+
+    let mut data = (categories.len() as u32).to_le_bytes().to_vec();
+    for (name, _, _, _) in categories {
+        let mut buf = [0; 32];
+        buf[..name.len()].copy_from_slice(name.as_bytes());
+        data.extend(buf);
+    }
+    for info in table {
+        data.extend(info.to_le_bytes());
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let data = encode_char_property(
+            &[
+                ("DEFAULT", 0, 1, 0),
+                ("SPACE", 0, 1, 0),
+                ("KANJI", 0, 0, 2),
+                ("KANJINUMERIC", 1, 1, 0),
+            ],
+            &[
+                (0x20, 0x20, &[1]),
+                (0x4e00, 0x9fa5, &[2]),
+                (0x4e00, 0x4e00, &[3, 2]),
+            ],
+        );
+
+        let mut def = Vec::new();
+        decode_char_property(&data, &mut def).unwrap();
+        assert_eq!(
+            String::from_utf8(def).unwrap(),
+            concat!(
+                "DEFAULT 0 1 0\n",
+                "SPACE 0 1 0\n",
+                "KANJI 0 0 2\n",
+                "KANJINUMERIC 1 1 0\n",
+                "0x0020 SPACE\n",
+                "0x4E00 KANJINUMERIC KANJI\n",
+                "0x4E01..0x9FA5 KANJI\n",
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert!(decode_char_property(&[1, 0, 0, 0], Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use byteorder::{ByteOrder, LittleEndian};
+use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
+
+/// Longest key accepted while walking the trie, to stop on broken arrays.
+const MAX_KEY_LENGTH: usize = 1024;
+
+/// Enumerates the keys and values of a Darts double array, the trie of MeCab dictionaries.
+///
+/// Each unit is a pair of `base: i32` and `check: u32`.
+/// A byte `c` moves from the node `b` to `b + c + 1`, and a key ends at `b`
+/// with the value `-base - 1`. Keys are returned in byte order.
+pub(super) fn entries(data: &[u8]) -> LinderaResult<Vec<(Vec<u8>, u32)>> {
+    if !data.len().is_multiple_of(8) || data.is_empty() {
+        return Err(LinderaErrorKind::Deserialize
+            .with_error(anyhow::anyhow!("Invalid double array size: {}", data.len())));
+    }
+    let units = data
+        .chunks_exact(8)
+        .map(|unit| {
+            (
+                LittleEndian::read_i32(&unit[0..4]),
+                LittleEndian::read_u32(&unit[4..8]),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+    let mut stack = vec![(units[0].0, Vec::new())];
+    while let Some((base, key)) = stack.pop() {
+        if key.len() > MAX_KEY_LENGTH || base < 0 {
+            return Err(
+                LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!("Broken double array"))
+            );
+        }
+        let b = base as usize;
+
+        if let Some(&(value, check)) = units.get(b) {
+            if check as usize == b && value < 0 {
+                result.push((key.clone(), (-value - 1) as u32));
+            }
+        }
+
+        for c in (0..=u8::MAX).rev() {
+            let p = b + c as usize + 1;
+            if let Some(&(next, check)) = units.get(p) {
+                if check as usize == b && next >= 0 {
+                    let mut next_key = key.clone();
+                    next_key.push(c);
+                    stack.push((next, next_key));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Builds a double array from sorted unique keys.
+#[cfg(test)]
+pub(super) fn build(keys: &[(&[u8], u32)]) -> Vec<u8> {
+    use std::collections::HashSet;
+
+    fn place(
+        units: &mut Vec<(i32, u32)>,
+        used: &mut HashSet<usize>,
+        keys: &[(&[u8], u32)],
+        depth: usize,
+    ) -> usize {
+        let mut groups: Vec<(usize, usize, usize)> = Vec::new();
+        for (i, (key, _)) in keys.iter().enumerate() {
+            let label = key.get(depth).map_or(0, |&c| c as usize + 1);
+            match groups.last_mut() {
+                Some((last, _, end)) if *last == label => *end = i + 1,
+                _ => groups.push((label, i, i + 1)),
+            }
+        }
+
+        let base = (1..)
+            .find(|b| {
+                !used.contains(b)
+                    && groups
+                        .iter()
+                        .all(|(label, _, _)| units.get(b + label).is_none_or(|u| u.1 == 0))
+            })
+            .unwrap();
+        used.insert(base);
+
+        let last = base + groups.last().unwrap().0;
+        if units.len() <= last {
+            units.resize(last + 1, (0, 0));
+        }
+        for (label, _, _) in &groups {
+            units[base + label].1 = base as u32;
+        }
+        for (label, start, end) in groups {
+            units[base + label].0 = if label == 0 {
+                -(keys[start].1 as i32) - 1
+            } else {
+                place(units, used, &keys[start..end], depth + 1) as i32
+            };
+        }
+        base
+    }
+
+    let mut units = vec![(0, 0)];
+    let root = place(&mut units, &mut HashSet::new(), keys, 0);
+    units[0].0 = root as i32;
+
+    let mut data = vec![0; units.len() * 8];
+    for (i, (base, check)) in units.into_iter().enumerate() {
+        LittleEndian::write_i32(&mut data[i * 8..], base);
+        LittleEndian::write_u32(&mut data[i * 8 + 4..], check);
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let keys: Vec<(&[u8], u32)> = vec![
+            (b"a", 0),
+            (b"ab", 1),
+            (b"b", 2),
+            ("日本".as_bytes(), 3 << 8 | 2),
+            ("日本語".as_bytes(), 5 << 8 | 1),
+        ];
+        let data = build(&keys);
+
+        let entries = entries(&data).unwrap();
+        assert_eq!(
+            entries,
+            keys.iter()
+                .map(|(key, value)| (key.to_vec(), *value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert!(entries(&[0; 12]).is_err());
+    }
+}
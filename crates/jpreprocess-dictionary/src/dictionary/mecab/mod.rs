@@ -0,0 +1,521 @@
+//! Reader of compiled MeCab dictionaries.
+//!
+//! OpenJTalk dictionaries such as `open_jtalk_dic_utf_8` consist of `sys.dic`, `unk.dic`,
+//! `matrix.bin` and `char.bin`. They can be
+//!
+//! - converted back into dictionary sources with [`decompile_mecab_dictionary`],
+//! - built into a jpreprocess dictionary with
+//!   [`JPreprocessDictionaryBuilder::build_dictionary_from_mecab`], or
+//! - loaded as a system dictionary with [`load_mecab_dictionary`], or with
+//!   [`load_mecab_dictionary_cached`] to convert it only once.
+
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use byteorder::{ByteOrder, LittleEndian};
+use encoding_rs::Encoding;
+use lindera_dictionary::{dictionary::Dictionary, error::LinderaErrorKind, LinderaResult};
+
+use crate::util::TempDir;
+
+use super::{
+    to_dict::JPreprocessDictionaryBuilder,
+    word_encoding::{DictionaryWordEncoding, JPreprocessDictionaryWordEncoding},
+};
+
+mod char_property;
+mod double_array;
+
+pub use char_property::decode_char_property;
+
+const DICTIONARY_MAGIC_ID: u32 = 0xef718f77;
+const DICTIONARY_VERSION: u32 = 102;
+const HEADER_SIZE: usize = 4 * 10 + 32;
+const TOKEN_SIZE: usize = 16;
+
+/// Kind of a compiled MeCab dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MecabDictionaryType {
+    /// `sys.dic`
+    System,
+    /// User dictionary compiled with `mecab-dict-index -u`
+    User,
+    /// `unk.dic`
+    Unknown,
+}
+
+/// An entry of a compiled MeCab dictionary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MecabEntry {
+    pub surface: String,
+    pub left_context_id: u16,
+    pub right_context_id: u16,
+    pub cost: i16,
+    /// The comma-separated features (e.g. `名詞,固有名詞,地域,国,*,*,日本,ニッポン,ニッポン,3/4,C1,-1`).
+    pub feature: String,
+}
+
+/// A compiled MeCab dictionary (`sys.dic`, `unk.dic` or a user dictionary).
+#[derive(Clone, Debug)]
+pub struct MecabDictionary {
+    pub dictionary_type: MecabDictionaryType,
+    /// The charset of the source, used to decode surfaces and features.
+    pub charset: String,
+    pub entries: Vec<MecabEntry>,
+}
+
+impl MecabDictionary {
+    pub fn read(path: &Path) -> LinderaResult<Self> {
+        Self::load(&read_file(path)?)
+            .map_err(|err| err.add_context(format!("Failed to read {path:?}")))
+    }
+
+    pub fn load(data: &[u8]) -> LinderaResult<Self> {
+        if data.len() < HEADER_SIZE {
+            return Err(invalid("The file is too short"));
+        }
+        let header = |i: usize| LittleEndian::read_u32(&data[i * 4..]);
+
+        if (header(0) ^ DICTIONARY_MAGIC_ID) as usize != data.len() {
+            return Err(invalid("Invalid magic number"));
+        }
+        if header(1) != DICTIONARY_VERSION {
+            return Err(invalid(format!(
+                "Unsupported version {} (expected {DICTIONARY_VERSION})",
+                header(1)
+            )));
+        }
+        let dictionary_type = match header(2) {
+            0 => MecabDictionaryType::System,
+            1 => MecabDictionaryType::User,
+            2 => MecabDictionaryType::Unknown,
+            t => return Err(invalid(format!("Unknown dictionary type {t}"))),
+        };
+        let (da_size, token_size, feature_size) =
+            (header(6) as usize, header(7) as usize, header(8) as usize);
+
+        let charset = {
+            let field = &data[40..HEADER_SIZE];
+            let end = field.iter().position(|&c| c == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+        let encoding = charset_encoding(&charset)
+            .ok_or_else(|| invalid(format!("Unsupported charset {charset}")))?;
+
+        if data.len() != HEADER_SIZE + da_size + token_size + feature_size {
+            return Err(invalid("Section sizes do not match the file size"));
+        }
+        let da = &data[HEADER_SIZE..HEADER_SIZE + da_size];
+        let tokens = &data[HEADER_SIZE + da_size..HEADER_SIZE + da_size + token_size];
+        let features = &data[HEADER_SIZE + da_size + token_size..];
+
+        let mut entries = Vec::new();
+        for (key, value) in double_array::entries(da)? {
+            let surface = encoding.decode_without_bom_handling(&key).0.into_owned();
+
+            // The value is the index of the first token and the number of tokens.
+            let first = (value >> 8) as usize;
+            let count = (value & 0xff) as usize;
+            for i in first..first + count {
+                let token = tokens
+                    .get(i * TOKEN_SIZE..(i + 1) * TOKEN_SIZE)
+                    .ok_or_else(|| invalid(format!("Token {i} is out of range")))?;
+                let offset = LittleEndian::read_u32(&token[8..12]) as usize;
+                let feature = features
+                    .get(offset..)
+                    .and_then(|f| f.iter().position(|&c| c == 0).map(|end| &f[..end]))
+                    .ok_or_else(|| invalid(format!("Feature of token {i} is out of range")))?;
+
+                entries.push(MecabEntry {
+                    surface: surface.clone(),
+                    left_context_id: LittleEndian::read_u16(&token[0..2]),
+                    right_context_id: LittleEndian::read_u16(&token[2..4]),
+                    cost: LittleEndian::read_i16(&token[6..8]),
+                    feature: encoding.decode_without_bom_handling(feature).0.into_owned(),
+                });
+            }
+        }
+
+        Ok(Self {
+            dictionary_type,
+            charset,
+            entries,
+        })
+    }
+
+    /// Writes the entries as MeCab dictionary source in UTF-8
+    /// (`surface,left_context_id,right_context_id,cost,features`).
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> LinderaResult<()> {
+        for entry in &self.entries {
+            let surface = if entry.surface.contains([',', '"']) {
+                format!("\"{}\"", entry.surface.replace('"', "\"\""))
+            } else {
+                entry.surface.clone()
+            };
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                surface, entry.left_context_id, entry.right_context_id, entry.cost, entry.feature
+            )
+            .map_err(|err| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `matrix.bin` of a MeCab dictionary and writes it as `matrix.def`.
+pub fn decode_matrix<W: Write>(data: &[u8], mut writer: W) -> LinderaResult<()> {
+    if data.len() < 4 {
+        return Err(invalid("Invalid matrix.bin size"));
+    }
+    let left_size = LittleEndian::read_u16(&data[0..2]) as usize;
+    let right_size = LittleEndian::read_u16(&data[2..4]) as usize;
+    if data.len() != 4 + 2 * left_size * right_size {
+        return Err(invalid("Invalid matrix.bin size"));
+    }
+
+    let write_err = |err: std::io::Error| LinderaErrorKind::Io.with_error(anyhow::anyhow!(err));
+    writeln!(writer, "{left_size} {right_size}").map_err(write_err)?;
+    for left in 0..left_size {
+        for right in 0..right_size {
+            let cost = LittleEndian::read_i16(&data[4 + 2 * (left + left_size * right)..]);
+            writeln!(writer, "{left} {right} {cost}").map_err(write_err)?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts a compiled MeCab dictionary in `input_dir` into dictionary sources in `output_dir`.
+///
+/// `sys.dic`, `unk.dic`, `matrix.bin` and `char.bin` are written as
+/// `lex.csv`, `unk.def`, `matrix.def` and `char.def` in UTF-8.
+pub fn decompile_mecab_dictionary(input_dir: &Path, output_dir: &Path) -> LinderaResult<()> {
+    fs::create_dir_all(output_dir).map_err(|err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to create {output_dir:?}"))
+    })?;
+
+    MecabDictionary::read(&input_dir.join("sys.dic"))?
+        .write_csv(create_file(&output_dir.join("lex.csv"))?)?;
+    MecabDictionary::read(&input_dir.join("unk.dic"))?
+        .write_csv(create_file(&output_dir.join("unk.def"))?)?;
+    decode_matrix(
+        &read_file(&input_dir.join("matrix.bin"))?,
+        create_file(&output_dir.join("matrix.def"))?,
+    )?;
+    decode_char_property(
+        &read_file(&input_dir.join("char.bin"))?,
+        create_file(&output_dir.join("char.def"))?,
+    )?;
+
+    Ok(())
+}
+
+/// Loads a compiled MeCab dictionary (e.g. `open_jtalk_dic_utf_8`) as a system dictionary.
+///
+/// The dictionary is converted into a jpreprocess dictionary on every call, which takes a while
+/// for a full naist-jdic. Use [`load_mecab_dictionary_cached`] to keep the conversion.
+pub fn load_mecab_dictionary(input_dir: &Path) -> LinderaResult<Dictionary> {
+    let output = TempDir::new()?;
+    JPreprocessDictionaryBuilder::default()
+        .build_dictionary_from_mecab(input_dir, output.path())?;
+    Dictionary::load_from_path(output.path())
+}
+
+/// Loads a compiled MeCab dictionary as a system dictionary, converting it only once.
+///
+/// The dictionary is converted into a jpreprocess dictionary under `cache_dir` on the first load,
+/// which takes a while for a full naist-jdic, and later loads read the converted dictionary.
+/// The conversion is redone when the path, the size or the modification time of the MeCab files
+/// or the version of JPreprocess changes. Outdated conversions are left in `cache_dir`.
+pub fn load_mecab_dictionary_cached(
+    input_dir: &Path,
+    cache_dir: &Path,
+) -> LinderaResult<Dictionary> {
+    let converted = cache_dir.join(format!("jpreprocess-mecab-{}", conversion_key(input_dir)?));
+    if !converted.is_dir() {
+        let output = TempDir::new()?;
+        JPreprocessDictionaryBuilder::default()
+            .build_dictionary_from_mecab(input_dir, output.path())?;
+        // Another process may have converted the same dictionary in the meantime.
+        if let Err(err) = fs::rename(output.path(), &converted) {
+            if !converted.is_dir() {
+                return Err(LinderaErrorKind::Io
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Failed to create {converted:?}")));
+            }
+        }
+    }
+    Dictionary::load_from_path(&converted)
+}
+
+/// Identifies the conversion of the MeCab dictionary by the files and the word encoding,
+/// whose identifier contains the version of JPreprocess.
+///
+/// The key is an MD5 digest, so it stays the same across builds and Rust versions.
+fn conversion_key(input_dir: &Path) -> LinderaResult<String> {
+    let mut context = md5::Context::new();
+    context.consume(JPreprocessDictionaryWordEncoding::identifier());
+    context.consume([0]);
+
+    let input_dir = canonicalize(input_dir)?;
+    context.consume(input_dir.as_os_str().as_encoded_bytes());
+    context.consume([0]);
+    for name in ["sys.dic", "unk.dic", "matrix.bin", "char.bin"] {
+        let path = input_dir.join(name);
+        let metadata = fs::metadata(&path).map_err(|err| {
+            LinderaErrorKind::Io
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to read {path:?}"))
+        })?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        context.consume(metadata.len().to_le_bytes());
+        context.consume(modified.as_secs().to_le_bytes());
+        context.consume(modified.subsec_nanos().to_le_bytes());
+    }
+
+    Ok(format!("{:x}", context.finalize()))
+}
+
+fn canonicalize(path: &Path) -> LinderaResult<PathBuf> {
+    path.canonicalize().map_err(|err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to read {path:?}"))
+    })
+}
+
+fn charset_encoding(charset: &str) -> Option<&'static Encoding> {
+    Encoding::for_label_no_replacement(charset.as_bytes()).or_else(|| {
+        // mecab-dict-index accepts names such as `SHIFT-JIS` and `EUC_JP`.
+        match charset.to_ascii_lowercase().replace('_', "-").as_str() {
+            "shift-jis" | "sjis" => Some(encoding_rs::SHIFT_JIS),
+            "euc-jp" => Some(encoding_rs::EUC_JP),
+            "utf8" | "utf-8" => Some(encoding_rs::UTF_8),
+            _ => None,
+        }
+    })
+}
+
+fn invalid<M: std::fmt::Display>(message: M) -> lindera_dictionary::error::LinderaError {
+    LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!("{message}"))
+}
+
+fn read_file(path: &Path) -> LinderaResult<Vec<u8>> {
+    fs::read(path).map_err(|err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to read {path:?}"))
+    })
+}
+
+fn create_file(path: &Path) -> LinderaResult<BufWriter<File>> {
+    File::create(path).map(BufWriter::new).map_err(|err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to create {path:?}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::{
+        token::{Token, Tokenizer},
+        word_entry::WordEntry,
+    };
+
+    use crate::tokenizer::default::DefaultTokenizer;
+
+    use super::*;
+
+    fn encode_dictionary(dictionary_type: u32, entries: &[(&str, u16, u16, i16, &str)]) -> Vec<u8> {
+        let mut entries = entries.to_vec();
+        entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+        let mut keys: Vec<(&[u8], u32)> = Vec::new();
+        let mut tokens = Vec::new();
+        let mut features = Vec::new();
+        for (i, (surface, left, right, cost, feature)) in entries.iter().enumerate() {
+            match keys.last_mut() {
+                Some((key, value)) if *key == surface.as_bytes() => *value += 1,
+                _ => keys.push((surface.as_bytes(), (i as u32) << 8 | 1)),
+            }
+            tokens.extend(left.to_le_bytes());
+            tokens.extend(right.to_le_bytes());
+            tokens.extend(0u16.to_le_bytes());
+            tokens.extend(cost.to_le_bytes());
+            tokens.extend((features.len() as u32).to_le_bytes());
+            tokens.extend(0u32.to_le_bytes());
+            features.extend(feature.as_bytes());
+            features.push(0);
+        }
+        let da = double_array::build(&keys);
+
+        let size = HEADER_SIZE + da.len() + tokens.len() + features.len();
+        let mut data = Vec::new();
+        for value in [
+            DICTIONARY_MAGIC_ID ^ size as u32,
+            DICTIONARY_VERSION,
+            dictionary_type,
+            entries.len() as u32,
+            2,
+            2,
+            da.len() as u32,
+            tokens.len() as u32,
+            features.len() as u32,
+            0,
+        ] {
+            data.extend(value.to_le_bytes());
+        }
+        let mut charset = [0; 32];
+        charset[..5].copy_from_slice(b"utf-8");
+        data.extend(charset);
+        data.extend(da);
+        data.extend(tokens);
+        data.extend(features);
+        data
+    }
+
+    fn write_mecab_dictionary(dir: &Path) {
+        fs::write(
+            dir.join("sys.dic"),
+            encode_dictionary(
+                0,
+                &[
+                    (
+                        "日本",
+                        1,
+                        1,
+                        100,
+                        "名詞,固有名詞,地域,国,*,*,日本,ニホン,ニホン,2/3,C1,-1",
+                    ),
+                    ("語", 1, 1, 100, "名詞,接尾,一般,*,*,*,語,ゴ,ゴ,1/1,C1,-1"),
+                    (
+                        "日本語",
+                        1,
+                        1,
+                        1000,
+                        "名詞,一般,*,*,*,*,日本語,ニホンゴ,ニホンゴ,0/4,C1,-1",
+                    ),
+                ],
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("unk.dic"),
+            encode_dictionary(
+                2,
+                &[
+                    ("DEFAULT", 1, 1, 1000, "名詞,一般,*,*,*,*,*,*,*,*,*,*"),
+                    ("KANJI", 1, 1, 1000, "名詞,一般,*,*,*,*,*,*,*,*,*,*"),
+                ],
+            ),
+        )
+        .unwrap();
+        let mut matrix = [2u16, 2].map(u16::to_le_bytes).concat();
+        matrix.extend([0i16, 0, 0, 0].map(i16::to_le_bytes).concat());
+        fs::write(dir.join("matrix.bin"), matrix).unwrap();
+        fs::write(
+            dir.join("char.bin"),
+            char_property::encode_char_property(
+                &[("DEFAULT", 0, 1, 0), ("KANJI", 0, 0, 2)],
+                &[(0x4e00, 0x9fa5, &[1])],
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn load() {
+        let data = encode_dictionary(
+            0,
+            &[("a,b", 1, 2, -3, "名詞,一般"), ("あ", 4, 5, 6, "感動詞")],
+        );
+        let dictionary = MecabDictionary::load(&data).unwrap();
+        assert_eq!(dictionary.dictionary_type, MecabDictionaryType::System);
+        assert_eq!(dictionary.charset, "utf-8");
+        assert_eq!(
+            dictionary.entries[1],
+            MecabEntry {
+                surface: "あ".to_string(),
+                left_context_id: 4,
+                right_context_id: 5,
+                cost: 6,
+                feature: "感動詞".to_string(),
+            }
+        );
+
+        let mut csv = Vec::new();
+        dictionary.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "\"a,b\",1,2,-3,名詞,一般\nあ,4,5,6,感動詞\n"
+        );
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let mut data = encode_dictionary(0, &[("あ", 4, 5, 6, "感動詞")]);
+        data.push(0);
+        assert!(MecabDictionary::load(&data).is_err());
+    }
+
+    #[test]
+    fn matrix() {
+        let mut data = [2u16, 1].map(u16::to_le_bytes).concat();
+        data.extend([10i16, -20].map(i16::to_le_bytes).concat());
+
+        let mut def = Vec::new();
+        decode_matrix(&data, &mut def).unwrap();
+        assert_eq!(String::from_utf8(def).unwrap(), "2 1\n0 0 10\n1 0 -20\n");
+    }
+
+    #[test]
+    fn load_dictionary() {
+        let input = TempDir::new().unwrap();
+        write_mecab_dictionary(input.path());
+
+        // The key is an MD5 digest in hex.
+        assert_eq!(conversion_key(input.path()).unwrap().len(), 32);
+
+        let cache = TempDir::new().unwrap();
+        let dictionary = load_mecab_dictionary_cached(input.path(), cache.path()).unwrap();
+        let converted = || fs::read_dir(cache.path()).unwrap().count();
+        assert_eq!(converted(), 1);
+
+        // The dictionary is converted again only when the files change.
+        load_mecab_dictionary_cached(input.path(), cache.path()).unwrap();
+        assert_eq!(converted(), 1);
+        File::options()
+            .append(true)
+            .open(input.path().join("sys.dic"))
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH)
+            .unwrap();
+        load_mecab_dictionary_cached(input.path(), cache.path()).unwrap();
+        assert_eq!(converted(), 2);
+
+        let segmenter =
+            lindera::segmenter::Segmenter::new(lindera::mode::Mode::Normal, dictionary, None);
+        let tokenizer =
+            DefaultTokenizer::new(lindera::tokenizer::Tokenizer::new(segmenter)).unwrap();
+
+        let mut tokens = tokenizer.tokenize("日本語").unwrap();
+        assert_eq!(tokens.len(), 2);
+        let (surface, entry) = tokens[0].fetch().unwrap();
+        assert_eq!(surface, "日本");
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pron.to_string(), "ニホン");
+        assert_eq!(details.pron.accent(), 2);
+    }
+}
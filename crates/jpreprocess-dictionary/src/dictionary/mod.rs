@@ -1,2 +1,11 @@
+pub mod archive;
+pub mod convert;
+pub mod cost;
+pub mod diff;
+pub mod embed;
+pub mod export;
+pub mod mecab;
+pub mod subset;
 pub mod to_dict;
+pub mod user;
 pub mod word_encoding;
@@ -0,0 +1,305 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use lindera::dictionary::load_fs_dictionary;
+use lindera_dictionary::{
+    dictionary::Dictionary,
+    error::LinderaErrorKind,
+    mode::Mode,
+    viterbi::{Lattice, LexType},
+    LinderaResult,
+};
+
+use crate::util::TempDir;
+
+use super::{
+    cost::SegmentationChange,
+    export::{export_prefix_dictionary, write_csv, ExportedWord},
+    to_dict::JPreprocessDictionaryBuilder,
+};
+
+/// The result of [`subset_dictionary_source`].
+#[derive(Clone, Debug)]
+pub struct DictionarySubset {
+    /// The words kept, with the original context ids.
+    pub words: Vec<ExportedWord>,
+    /// The number of context ids after remapping.
+    pub context_ids: usize,
+    /// The sentences segmented differently with the subset.
+    pub changes: Vec<SegmentationChange>,
+}
+
+/// Cuts a dictionary source down to the words chosen in segmenting the sentences.
+///
+/// The source directory is built with `builder` to segment the sentences.
+/// The chosen words are written to `subset.csv` in `output_dir`, along with `unk.def` with all the
+/// unknown word definitions, and `matrix.def` only with the context ids used by them.
+/// The context ids are renumbered from 0, keeping their order.
+/// `char.def` and `feature.def` are copied as is.
+pub fn subset_dictionary_source(
+    builder: &JPreprocessDictionaryBuilder,
+    src_dir: &Path,
+    sentences: &[String],
+    output_dir: &Path,
+) -> LinderaResult<DictionarySubset> {
+    let work_dir = TempDir::new()?;
+
+    let full_dir = work_dir.path().join("full");
+    builder.build_dictionary(src_dir, &full_dir)?;
+    let full = load_fs_dictionary(&full_dir)?;
+
+    let words = corpus_words(&full, sentences)?;
+    let context_ids = write_subset_source(src_dir, &words, output_dir)?;
+
+    let subset_dir = work_dir.path().join("subset");
+    builder.build_dictionary(output_dir, &subset_dir)?;
+    let subset = load_fs_dictionary(&subset_dir)?;
+
+    let changes = sentences
+        .iter()
+        .filter_map(|sentence| {
+            let before = segment_surfaces(&full, sentence);
+            let after = segment_surfaces(&subset, sentence);
+            (before != after).then(|| SegmentationChange {
+                sentence: sentence.clone(),
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    Ok(DictionarySubset {
+        words,
+        context_ids,
+        changes,
+    })
+}
+
+/// The words of the system dictionary chosen in segmenting the sentences, ordered by word id.
+pub fn corpus_words(
+    dictionary: &Dictionary,
+    sentences: &[String],
+) -> LinderaResult<Vec<ExportedWord>> {
+    let mut lattice = Lattice::default();
+    let mut word_ids = BTreeSet::new();
+    for sentence in sentences {
+        lattice.set_text(
+            &dictionary.prefix_dictionary,
+            &None,
+            &dictionary.character_definition,
+            &dictionary.unknown_dictionary,
+            &dictionary.connection_cost_matrix,
+            sentence,
+            &Mode::Normal,
+        );
+        word_ids.extend(
+            lattice
+                .tokens_offset()
+                .into_iter()
+                .filter(|(_, word_id)| word_id.lex_type == LexType::System)
+                .map(|(_, word_id)| word_id.id),
+        );
+    }
+
+    Ok(export_prefix_dictionary(&dictionary.prefix_dictionary)?
+        .into_iter()
+        .filter(|word| word_ids.contains(&word.word_id))
+        .collect())
+}
+
+/// Writes a dictionary source with only the words, and returns the number of context ids.
+///
+/// See [`subset_dictionary_source`] for the files written.
+pub fn write_subset_source(
+    src_dir: &Path,
+    words: &[ExportedWord],
+    output_dir: &Path,
+) -> LinderaResult<usize> {
+    let unk = read(&src_dir.join("unk.def"))?;
+    let unk_rows = unk
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut ids = BTreeSet::new();
+    for word in words {
+        ids.insert(word.left_context_id);
+        ids.insert(word.right_context_id);
+    }
+    for row in &unk_rows {
+        for id in row.iter().skip(1).take(2) {
+            ids.insert(parse_id(id, "unk.def")?);
+        }
+    }
+    let mapping = ids
+        .into_iter()
+        .enumerate()
+        .map(|(new_id, id)| (id, new_id as u16))
+        .collect::<BTreeMap<_, _>>();
+
+    fs::create_dir_all(output_dir).map_err(io_error(output_dir))?;
+
+    let remapped = words
+        .iter()
+        .map(|word| ExportedWord {
+            left_context_id: mapping[&word.left_context_id],
+            right_context_id: mapping[&word.right_context_id],
+            ..word.clone()
+        })
+        .collect::<Vec<_>>();
+    let csv_path = output_dir.join("subset.csv");
+    write_csv(
+        &remapped,
+        fs::File::create(&csv_path).map_err(io_error(&csv_path))?,
+    )?;
+
+    let mut unk_def = String::new();
+    for mut row in unk_rows {
+        let left = mapping[&parse_id(row[1], "unk.def")?].to_string();
+        let right = mapping[&parse_id(row[2], "unk.def")?].to_string();
+        row[1] = &left;
+        row[2] = &right;
+        unk_def.push_str(&row.join(","));
+        unk_def.push('\n');
+    }
+    write(&output_dir.join("unk.def"), &unk_def)?;
+
+    let matrix = read(&src_dir.join("matrix.def"))?;
+    let mut matrix_def = format!("{} {}\n", mapping.len(), mapping.len());
+    for line in matrix.lines().skip(1) {
+        let mut parts = line.split_whitespace();
+        let (Some(left), Some(right), Some(cost)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if let (Some(left), Some(right)) = (
+            mapping.get(&parse_id(left, "matrix.def")?),
+            mapping.get(&parse_id(right, "matrix.def")?),
+        ) {
+            matrix_def.push_str(&format!("{left} {right} {cost}\n"));
+        }
+    }
+    write(&output_dir.join("matrix.def"), &matrix_def)?;
+
+    for name in ["char.def", "feature.def"] {
+        let path = src_dir.join(name);
+        if path.exists() {
+            fs::copy(&path, output_dir.join(name)).map_err(io_error(&path))?;
+        }
+    }
+
+    Ok(mapping.len())
+}
+
+/// Segments the text, and returns the surfaces of the tokens.
+fn segment_surfaces(dictionary: &Dictionary, text: &str) -> Vec<String> {
+    let mut lattice = Lattice::default();
+    lattice.set_text(
+        &dictionary.prefix_dictionary,
+        &None,
+        &dictionary.character_definition,
+        &dictionary.unknown_dictionary,
+        &dictionary.connection_cost_matrix,
+        text,
+        &Mode::Normal,
+    );
+
+    let offsets = lattice.tokens_offset();
+    offsets
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, _))| {
+            let end = offsets.get(i + 1).map_or(text.len(), |&(end, _)| end);
+            text[start..end].to_string()
+        })
+        .collect()
+}
+
+fn parse_id(id: &str, file_name: &str) -> LinderaResult<u16> {
+    id.trim().parse().map_err(|err: std::num::ParseIntError| {
+        LinderaErrorKind::Parse
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Invalid context id {id:?} in {file_name}"))
+    })
+}
+
+fn io_error(
+    path: &Path,
+) -> impl '_ + FnOnce(std::io::Error) -> lindera_dictionary::error::LinderaError {
+    move |err| {
+        LinderaErrorKind::Io
+            .with_error(anyhow::anyhow!(err))
+            .add_context(format!("Failed to access {path:?}"))
+    }
+}
+
+fn read(path: &Path) -> LinderaResult<String> {
+    fs::read_to_string(path).map_err(io_error(path))
+}
+
+fn write(path: &Path, content: &str) -> LinderaResult<()> {
+    fs::write(path, content).map_err(io_error(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subset() {
+        let src = TempDir::new().unwrap();
+        for (name, content) in [
+            (
+                "matrix.def",
+                "3 3\n0 0 0\n0 1 10\n0 2 20\n1 0 30\n1 1 40\n1 2 50\n2 0 60\n2 1 70\n2 2 80\n",
+            ),
+            ("char.def", "DEFAULT 0 0 1\nSPACE 0 1 0\n\n0x0020 SPACE\n"),
+            (
+                "unk.def",
+                "DEFAULT,2,2,5000,記号,一般,*,*,*,*,*\nSPACE,2,2,5000,記号,空白,*,*,*,*,*\n",
+            ),
+            (
+                "words.csv",
+                concat!(
+                    "音声,1,1,100,名詞,一般,*,*,*,*,音声,オンセイ,オンセー,1/4,C1\n",
+                    "合成,1,1,100,名詞,サ変接続,*,*,*,*,合成,ゴウセイ,ゴーセー,0/4,C2\n",
+                    "解析,0,0,100,名詞,サ変接続,*,*,*,*,解析,カイセキ,カイセキ,0/4,C2\n",
+                ),
+            ),
+        ] {
+            fs::write(src.path().join(name), content).unwrap();
+        }
+
+        let out = TempDir::new().unwrap();
+        let subset = subset_dictionary_source(
+            &JPreprocessDictionaryBuilder::default(),
+            src.path(),
+            &["音声を合成".to_string()],
+            out.path(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            subset
+                .words
+                .iter()
+                .map(|word| word.surface.as_str())
+                .collect::<Vec<_>>(),
+            ["合成", "音声"]
+        );
+        assert_eq!(subset.context_ids, 2);
+        assert!(subset.changes.is_empty());
+
+        assert_eq!(
+            fs::read_to_string(out.path().join("matrix.def")).unwrap(),
+            "2 2\n0 0 40\n0 1 50\n1 0 70\n1 1 80\n"
+        );
+        assert!(fs::read_to_string(out.path().join("unk.def"))
+            .unwrap()
+            .starts_with("DEFAULT,1,1,5000,"));
+    }
+}
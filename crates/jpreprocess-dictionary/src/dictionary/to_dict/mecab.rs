@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use lindera_dictionary::{dictionary::metadata::Metadata, error::LinderaErrorKind, LinderaResult};
+
+use crate::{
+    dictionary::mecab::{decompile_mecab_dictionary, MecabDictionary},
+    util::TempDir,
+};
+
+use super::{JPreprocessDictionaryBuilder, SourceSchema};
+
+impl JPreprocessDictionaryBuilder {
+    /// Builds a dictionary from a compiled MeCab dictionary (e.g. `open_jtalk_dic_utf_8`).
+    ///
+    /// The directory must contain `sys.dic`, `unk.dic`, `matrix.bin` and `char.bin`,
+    /// and the features must follow the metadata schema.
+    pub fn build_dictionary_from_mecab(
+        &self,
+        input_dir: &Path,
+        output_dir: &Path,
+    ) -> LinderaResult<()> {
+        let source = TempDir::new()?;
+        decompile_mecab_dictionary(input_dir, source.path())?;
+
+        // The decompiled sources are always in UTF-8.
+        let builder = Self::new(Metadata {
+            encoding: "UTF-8".to_string(),
+            ..self.metadata.clone()
+        })
+        .with_source_schema(SourceSchema::NaistJdic);
+        builder.build_dictionary(source.path(), output_dir)
+    }
+
+    /// Builds a user dictionary from a MeCab user dictionary compiled with `mecab-dict-index -u`.
+    pub fn build_user_dictionary_from_mecab(
+        &self,
+        input_file: &Path,
+        output_file: &Path,
+    ) -> LinderaResult<()> {
+        let dictionary = MecabDictionary::read(input_file)?;
+
+        let mut source = Vec::new();
+        dictionary.write_csv(&mut source)?;
+        let rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(source.as_slice())
+            .records()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| LinderaErrorKind::Content.with_error(anyhow::anyhow!(err)))?;
+
+        let user_dict = self.build_user_dict_from_rows(rows)?;
+        lindera_dictionary::builder::user_dictionary::build_user_dictionary(user_dict, output_file)
+    }
+}
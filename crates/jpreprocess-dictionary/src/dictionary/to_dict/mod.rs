@@ -1,4 +1,4 @@
-use std::{fs, path::Path};
+use std::{collections::HashMap, fs, path::Path};
 
 use lindera_dictionary::{
     builder::{
@@ -17,31 +17,102 @@ use lindera_dictionary::{
 
 use crate::dictionary::to_dict::prefix_dictionary::{
     generate_user_prefix_dictionary,
-    parser::{
-        DefaultParser, DefaultParserOptions, UserDictionaryParser, UserDictionaryParserOptions,
-    },
+    parser::{CSVParser, DefaultParserOptions, UserDictionaryParser, UserDictionaryParserOptions},
+    unidic::UnidicParser,
     write_system_prefix_dictionary, CSVReaderOptions,
 };
 
-use super::word_encoding::JPreprocessDictionaryWordEncoding;
+use super::{
+    user::{PosPreset, WordContext},
+    word_encoding::JPreprocessDictionaryWordEncoding,
+};
 
+mod mecab;
 mod prefix_dictionary;
+mod validate;
+#[cfg(feature = "vibrato")]
+mod vibrato;
+
+pub(crate) use prefix_dictionary::build_user_prefix_dictionary;
+pub use validate::{ValidationIssue, ValidationIssueKind};
+
+/// Column layout of the source CSV files of a system dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SourceSchema {
+    /// naist-jdic with the jpreprocess accent fields, laid out by `Metadata::dictionary_schema`.
+    #[default]
+    NaistJdic,
+    /// `lex.csv` of UniDic (unidic-cwj and unidic-csj 3.x).
+    ///
+    /// POS, conjugations, pronunciation and accents (`aType`, `aConType`)
+    /// are converted into the naist-jdic schema.
+    Unidic,
+}
 
+/// Column layout of the user dictionary rows that do not follow the system dictionary source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum UserRowFormat {
+    /// Rows laid out by `Metadata::user_dictionary_schema`.
+    #[default]
+    Schema,
+    /// `surface,pronunciation,accent[,POS]`, where the accent is the accent nucleus and POS is a [`PosPreset`].
+    ///
+    /// The other fields are filled in from the POS, and the context ids and cost are taken from
+    /// [`JPreprocessDictionaryBuilder::with_preset_contexts`].
+    Simplified,
+}
+
+#[derive(Clone)]
 pub struct JPreprocessDictionaryBuilder {
-    metadata: Metadata,
+    pub(crate) metadata: Metadata,
+    source_schema: SourceSchema,
+    user_row_format: UserRowFormat,
+    pub(crate) preset_contexts: HashMap<PosPreset, WordContext>,
 }
 
 impl JPreprocessDictionaryBuilder {
     pub fn new(metadata: Metadata) -> Self {
-        Self { metadata }
+        Self {
+            metadata,
+            source_schema: SourceSchema::default(),
+            user_row_format: UserRowFormat::default(),
+            preset_contexts: HashMap::new(),
+        }
+    }
+
+    /// Sets the layout of the system dictionary source.
+    ///
+    /// User dictionary rows are still parsed with `Metadata::user_dictionary_schema` or as [`UserRowFormat::Simplified`].
+    pub fn with_source_schema(self, source_schema: SourceSchema) -> Self {
+        Self {
+            source_schema,
+            ..self
+        }
+    }
+
+    /// Sets the layout of the user dictionary rows that do not follow the system dictionary source.
+    pub fn with_user_row_format(self, user_row_format: UserRowFormat) -> Self {
+        Self {
+            user_row_format,
+            ..self
+        }
+    }
+
+    /// Sets the context ids and cost of simplified user dictionary rows (`surface,pronunciation,accent[,POS]`).
+    ///
+    /// The ids depend on the system dictionary, so take them with [`preset_contexts`](super::user::preset_contexts).
+    /// A simplified row whose POS is not in `preset_contexts` cannot be built.
+    pub fn with_preset_contexts(self, preset_contexts: HashMap<PosPreset, WordContext>) -> Self {
+        Self {
+            preset_contexts,
+            ..self
+        }
     }
 }
 
 impl Default for JPreprocessDictionaryBuilder {
     fn default() -> Self {
-        Self {
-            metadata: Self::default_metadata(),
-        }
+        Self::new(Self::default_metadata())
     }
 }
 
@@ -135,15 +206,10 @@ impl JPreprocessDictionaryBuilder {
             .unwrap();
         let rows = reader.load_csv_data(input_dir)?;
 
-        let parser = DefaultParserOptions::default()
-            .skip_invalid_cost_or_id(self.metadata.skip_invalid_cost_or_id)
-            .schema(self.metadata.dictionary_schema.clone())
-            .normalize_details(self.metadata.normalize_details)
-            .builder()
-            .unwrap();
-
-        write_system_prefix_dictionary::<DefaultParser, JPreprocessDictionaryWordEncoding>(
-            &parser, &rows, output_dir,
+        write_system_prefix_dictionary::<_, JPreprocessDictionaryWordEncoding>(
+            &self.system_dictionary_parser(),
+            &rows,
+            output_dir,
         )
     }
 
@@ -181,19 +247,41 @@ impl JPreprocessDictionaryBuilder {
         &self,
         rows: Vec<csv::StringRecord>,
     ) -> LinderaResult<UserDictionary> {
-        let parser = UserDictionaryParserOptions::default()
+        let dict = generate_user_prefix_dictionary::<
+            UserDictionaryParser,
+            JPreprocessDictionaryWordEncoding,
+        >(&self.user_dictionary_parser(), &rows)?;
+
+        Ok(UserDictionary { dict })
+    }
+
+    fn system_dictionary_parser(&self) -> Box<dyn CSVParser> {
+        let schema = match self.source_schema {
+            SourceSchema::NaistJdic => self.metadata.dictionary_schema.clone(),
+            SourceSchema::Unidic => UnidicParser::default_schema(),
+        };
+        let parser = DefaultParserOptions::default()
+            .skip_invalid_cost_or_id(self.metadata.skip_invalid_cost_or_id)
+            .schema(schema)
+            .normalize_details(self.metadata.normalize_details)
+            .builder()
+            .unwrap();
+
+        match self.source_schema {
+            SourceSchema::NaistJdic => Box::new(parser),
+            SourceSchema::Unidic => Box::new(UnidicParser::new(parser)),
+        }
+    }
+
+    fn user_dictionary_parser(&self) -> UserDictionaryParser {
+        UserDictionaryParserOptions::default()
             .user_dictionary_fields_num(self.metadata.user_dictionary_schema.field_count())
+            .simplified_rows(self.user_row_format == UserRowFormat::Simplified)
             .default_word_cost(self.metadata.default_word_cost)
             .default_left_context_id(self.metadata.default_left_context_id)
             .default_right_context_id(self.metadata.default_right_context_id)
-            .dictionary_parser(
-                DefaultParserOptions::default()
-                    .skip_invalid_cost_or_id(self.metadata.skip_invalid_cost_or_id)
-                    .schema(self.metadata.dictionary_schema.clone())
-                    .normalize_details(self.metadata.normalize_details)
-                    .builder()
-                    .unwrap(),
-            )
+            .preset_contexts(self.preset_contexts.clone())
+            .dictionary_parser(self.system_dictionary_parser())
             .user_dictionary_parser(
                 DefaultParserOptions::default()
                     .schema(self.metadata.user_dictionary_schema.clone())
@@ -202,14 +290,7 @@ impl JPreprocessDictionaryBuilder {
                     .unwrap(),
             )
             .builder()
-            .unwrap();
-
-        let dict = generate_user_prefix_dictionary::<
-            UserDictionaryParser,
-            JPreprocessDictionaryWordEncoding,
-        >(&parser, &rows)?;
-
-        Ok(UserDictionary { dict })
+            .unwrap()
     }
 }
 
@@ -356,4 +437,134 @@ mod tests {
             },]
         );
     }
+
+    #[test]
+    fn test_accent_user_dictionary() {
+        let builder = JPreprocessDictionaryBuilder::default()
+            .with_user_row_format(UserRowFormat::Simplified)
+            .with_preset_contexts(
+                [
+                    (
+                        PosPreset::ProperNoun,
+                        WordContext {
+                            left_context_id: 1288,
+                            right_context_id: 1288,
+                            word_cost: -10000,
+                        },
+                    ),
+                    (
+                        PosPreset::SahenNoun,
+                        WordContext {
+                            left_context_id: 1283,
+                            right_context_id: 1283,
+                            word_cost: 3000,
+                        },
+                    ),
+                ]
+                .into(),
+            );
+
+        let data = vec![
+            vec!["東京", "トーキョー", "0"],
+            vec!["検索", "ケンサク", "0", "サ変"],
+        ];
+
+        let user_dict = builder.build_user_dict_from_data(data).unwrap();
+        let words = crate::dictionary::export::export_prefix_dictionary(&user_dict.dict).unwrap();
+        let records = words
+            .iter()
+            .map(|word| word.to_record().join(","))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                "東京,1288,1288,-10000,名詞,固有名詞,一般,*,*,*,*,トーキョー,トーキョー,0/4,C1,-1",
+                "検索,1283,1283,3000,名詞,サ変接続,*,*,*,*,*,ケンサク,ケンサク,0/4,C2,-1",
+            ]
+        );
+
+        let issues = builder.validate_user_dict_from_data(vec![
+            vec!["大阪", "オーサカ", "5"],
+            vec!["奈良", "ナラ", "1", "動詞"],
+            vec!["京都", "キョウト", "キョート"],
+            vec!["神戸", "コーベ", "キョート", "固有名詞"],
+        ]);
+        assert_eq!(issues.len(), 4, "{issues:?}");
+
+        // Without the simplified format, rows of 3 fields follow the user dictionary schema.
+        let user_dict = JPreprocessDictionaryBuilder::default()
+            .build_user_dict_from_data(vec![vec!["東京", "トウキョウ", "0"]])
+            .unwrap();
+        let words = crate::dictionary::export::export_prefix_dictionary(&user_dict.dict).unwrap();
+        assert_eq!(words[0].details[1], "*");
+        assert_eq!(words[0].details[7], "トウキョウ");
+
+        // The system dictionary gave no context for 一般名詞.
+        assert!(builder
+            .build_user_dict_from_data(vec![vec!["東京", "トーキョー", "0", "一般名詞"]])
+            .is_err());
+        assert!(JPreprocessDictionaryBuilder::default()
+            .with_user_row_format(UserRowFormat::Simplified)
+            .build_user_dict_from_data(vec![vec!["東京", "トーキョー", "0"]])
+            .is_err());
+    }
+
+    #[test]
+    fn test_unidic_user_dictionary() {
+        let builder =
+            JPreprocessDictionaryBuilder::default().with_source_schema(SourceSchema::Unidic);
+
+        let mut row = vec![
+            "東京",
+            "10",
+            "10",
+            "3000",
+            "名詞",
+            "固有名詞",
+            "地名",
+            "一般",
+            "*",
+            "*",
+        ];
+        row.extend([
+            "トウキョウ",
+            "トウキョウ",
+            "東京",
+            "トーキョー",
+            "東京",
+            "トーキョー",
+            "固",
+            "*",
+            "*",
+            "*",
+            "*",
+            "*",
+            "*",
+            "体",
+            "トウキョウ",
+            "トウキョウ",
+            "トウキョウ",
+            "トウキョウ",
+            "0",
+            "C1",
+            "*",
+            "0",
+            "0",
+        ]);
+
+        let user_dict = builder.build_user_dict_from_data(vec![row]).unwrap();
+        assert_eq!(
+            user_dict.dict.find_surface("東京"),
+            vec![WordEntry {
+                word_id: WordId {
+                    id: 0,
+                    is_system: false,
+                    lex_type: LexType::User,
+                },
+                word_cost: 3000,
+                left_id: 10,
+                right_id: 10,
+            },]
+        );
+    }
 }
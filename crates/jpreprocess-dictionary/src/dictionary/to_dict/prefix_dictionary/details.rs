@@ -4,8 +4,11 @@ use byteorder::{LittleEndian, WriteBytesExt};
 use csv::StringRecord;
 use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
 
-use crate::dictionary::{
-    to_dict::prefix_dictionary::parser::CSVParser, word_encoding::DictionaryWordEncoding,
+use crate::{
+    dictionary::{
+        to_dict::prefix_dictionary::parser::CSVParser, word_encoding::DictionaryWordEncoding,
+    },
+    word_data::encode_preamble,
 };
 
 /// Generate word detail files (dict.words, dict.wordsidx)
@@ -20,8 +23,22 @@ where
     let mut dict_words_buffer = Vec::new();
     let mut dict_wordsidx_buffer = Vec::new();
 
+    // The surfaces are recorded so that the words can be exported.
+    // A row with an invalid surface is not in the dictionary, so its surface is left empty.
+    let surfaces = rows
+        .iter()
+        .map(|row| parser.surface(row).unwrap_or_default())
+        .collect::<Vec<_>>();
+    if let Some(surface) = surfaces.iter().find(|surface| surface.contains('\0')) {
+        return Err(LinderaErrorKind::Content
+            .with_error(anyhow::anyhow!("The surface {:?} contains NUL", surface)));
+    }
+
     dict_words_buffer
-        .write_all(E::identifier().as_bytes())
+        .write_all(&encode_preamble(
+            E::identifier(),
+            surfaces.iter().map(String::as_str),
+        ))
         .map_err(|err| {
             LinderaErrorKind::Io
                 .with_error(anyhow::anyhow!(err))
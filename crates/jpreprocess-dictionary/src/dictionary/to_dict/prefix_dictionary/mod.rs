@@ -22,6 +22,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -33,7 +34,7 @@ use encoding_rs::{Encoding, UTF_8};
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use glob::glob;
 use lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary;
-use lindera_dictionary::viterbi::LexType;
+use lindera_dictionary::viterbi::{LexType, WordEntry};
 use log::debug;
 
 use lindera_dictionary::error::LinderaErrorKind;
@@ -43,6 +44,7 @@ use crate::dictionary::word_encoding::DictionaryWordEncoding;
 
 pub mod details;
 pub mod parser;
+pub mod unidic;
 pub mod word_entry;
 
 use self::details::generate_words_files;
@@ -222,11 +224,19 @@ pub fn generate_user_prefix_dictionary<P: CSVParser, E: DictionaryWordEncoding>(
     rows: &[StringRecord],
 ) -> LinderaResult<PrefixDictionary> {
     let word_entry_map = build_word_entry_map(parser, rows, LexType::System)?;
+    let (words, wordsidx) = generate_words_files::<P, E>(parser, rows)?;
 
-    let da = generate_double_array(&word_entry_map, false)?;
-    let vals = generate_values(&word_entry_map)?;
+    build_user_prefix_dictionary(&word_entry_map, wordsidx, words)
+}
 
-    let (words, wordsidx) = generate_words_files::<P, E>(parser, rows)?;
+/// Builds a user prefix dictionary from the word entries by surface and the encoded words.
+pub(crate) fn build_user_prefix_dictionary(
+    word_entry_map: &BTreeMap<String, Vec<WordEntry>>,
+    wordsidx: Vec<u8>,
+    words: Vec<u8>,
+) -> LinderaResult<PrefixDictionary> {
+    let da = generate_double_array(word_entry_map, false)?;
+    let vals = generate_values(word_entry_map)?;
 
     PrefixDictionary::load(da, vals, wordsidx, words, false)
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use csv::StringRecord;
 use derive_builder::Builder;
@@ -6,6 +6,8 @@ use derive_builder::Builder;
 use jpreprocess_core::word_line::WordDetailsLine;
 use lindera_dictionary::dictionary::schema::Schema;
 
+use crate::dictionary::user::{PosPreset, WordContext};
+
 use thiserror::Error;
 
 pub trait CSVParser {
@@ -16,12 +18,32 @@ pub trait CSVParser {
     fn details(&self, row: &StringRecord) -> Result<WordDetailsLine, CSVParseError>;
 }
 
+impl<P: CSVParser + ?Sized> CSVParser for Box<P> {
+    fn surface(&self, row: &StringRecord) -> Result<String, CSVParseError> {
+        (**self).surface(row)
+    }
+    fn left_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
+        (**self).left_context_id(row)
+    }
+    fn right_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
+        (**self).right_context_id(row)
+    }
+    fn cost(&self, row: &StringRecord) -> Result<i16, CSVParseError> {
+        (**self).cost(row)
+    }
+    fn details(&self, row: &StringRecord) -> Result<WordDetailsLine, CSVParseError> {
+        (**self).details(row)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum CSVParseError {
     #[error("Invalid {0} value: {1}")]
     InvalidValue(CSVField, String),
     #[error("Field {0} not found")]
     FieldNotFound(CSVField),
+    #[error("No context ids for {0:?}; take them from a system dictionary")]
+    NoPresetContext(PosPreset),
 }
 
 #[derive(Error, Debug)]
@@ -30,6 +52,9 @@ pub enum CSVField {
     LeftContextId,
     RightContextId,
     Cost,
+    Pronunciation,
+    Accent,
+    PosPreset,
 }
 
 impl std::fmt::Display for CSVField {
@@ -39,6 +64,9 @@ impl std::fmt::Display for CSVField {
             CSVField::LeftContextId => write!(f, "left context ID"),
             CSVField::RightContextId => write!(f, "right context ID"),
             CSVField::Cost => write!(f, "cost"),
+            CSVField::Pronunciation => write!(f, "pronunciation"),
+            CSVField::Accent => write!(f, "accent"),
+            CSVField::PosPreset => write!(f, "POS"),
         }
     }
 }
@@ -66,7 +94,7 @@ impl Default for DefaultParser {
 
 impl DefaultParser {
     /// Get field value
-    fn get_field_value(&self, row: &StringRecord, field_name: &str) -> Option<String> {
+    pub(super) fn get_field_value(&self, row: &StringRecord, field_name: &str) -> Option<String> {
         if let Some(index) = self.schema.get_field_index(field_name) {
             if index >= row.len() {
                 return None;
@@ -196,6 +224,15 @@ impl CSVParser for DefaultParser {
     }
 }
 
+/// Parses user dictionary rows.
+///
+/// Besides the rows of the system dictionary schema, the rows of the user dictionary schema are accepted,
+/// or with `simplified_rows`, the rows of `surface,pronunciation,accent[,POS]` instead, where the
+/// accent is the accent nucleus and POS is a [`PosPreset`] (固有名詞 by default).
+/// The other fields of simplified rows are filled in from the POS.
+///
+/// The context ids and cost of simplified rows are taken from `preset_contexts`,
+/// and a row whose POS is not in it is an error, as the ids depend on the system dictionary.
 #[derive(Builder)]
 #[builder(pattern = "owned")]
 #[builder(name = UserDictionaryParserOptions)]
@@ -203,6 +240,9 @@ impl CSVParser for DefaultParser {
 pub struct UserDictionaryParser {
     #[builder(default = "3")]
     user_dictionary_fields_num: usize,
+    /// Parses the rows of 3 or 4 fields as simplified rows, instead of the user dictionary schema.
+    #[builder(default = "false")]
+    simplified_rows: bool,
 
     #[builder(default = "-10000")]
     default_word_cost: i16,
@@ -210,16 +250,65 @@ pub struct UserDictionaryParser {
     default_left_context_id: u16,
     #[builder(default = "0")]
     default_right_context_id: u16,
+    /// The context ids and cost of simplified rows for each POS.
+    #[builder(default = "HashMap::new()")]
+    preset_contexts: HashMap<PosPreset, WordContext>,
 
-    #[builder(default = "DefaultParser::default()")]
-    dictionary_parser: DefaultParser,
+    #[builder(default = "Box::new(DefaultParser::default())")]
+    dictionary_parser: Box<dyn CSVParser>,
     #[builder(default = "DefaultParser::default()")]
     user_dictionary_parser: DefaultParser,
 }
 
+impl UserDictionaryParser {
+    fn is_simple_row(&self, row: &StringRecord) -> bool {
+        self.simplified_rows && matches!(row.len(), 3 | 4)
+    }
+
+    /// The accent nucleus of a simplified row.
+    fn simple_accent(row: &StringRecord) -> Result<usize, CSVParseError> {
+        let accent = row[2].trim();
+        usize::from_str(accent)
+            .map_err(|_| CSVParseError::InvalidValue(CSVField::Accent, accent.to_string()))
+    }
+
+    fn preset(&self, row: &StringRecord) -> Result<PosPreset, CSVParseError> {
+        match row.get(3).map(str::trim) {
+            Some(preset) if !preset.is_empty() => PosPreset::from_str(preset)
+                .map_err(|_| CSVParseError::InvalidValue(CSVField::PosPreset, preset.to_string())),
+            _ => Ok(PosPreset::default()),
+        }
+    }
+
+    fn context(&self, row: &StringRecord) -> Result<WordContext, CSVParseError> {
+        let preset = self.preset(row)?;
+        self.preset_contexts
+            .get(&preset)
+            .copied()
+            .ok_or(CSVParseError::NoPresetContext(preset))
+    }
+
+    fn simple_details(&self, row: &StringRecord) -> Result<WordDetailsLine, CSVParseError> {
+        let pron = row[1].trim();
+        let accent = Self::simple_accent(row)?;
+        self.preset(row)?
+            .details(pron, Some(accent))
+            .map_err(|_| CSVParseError::InvalidValue(CSVField::Pronunciation, pron.to_string()))
+    }
+}
+
 impl CSVParser for UserDictionaryParser {
     fn surface(&self, row: &StringRecord) -> Result<String, CSVParseError> {
-        if row.len() == self.user_dictionary_fields_num {
+        if self.is_simple_row(row) {
+            let surface = row[0].trim();
+            if surface.is_empty() {
+                Err(CSVParseError::FieldNotFound(CSVField::Surface))
+            } else if self.user_dictionary_parser.normalize_details {
+                Ok(normalize(surface))
+            } else {
+                Ok(surface.to_string())
+            }
+        } else if row.len() == self.user_dictionary_fields_num {
             self.user_dictionary_parser.surface(row)
         } else {
             self.dictionary_parser.surface(row)
@@ -227,7 +316,9 @@ impl CSVParser for UserDictionaryParser {
     }
 
     fn cost(&self, row: &StringRecord) -> Result<i16, CSVParseError> {
-        if row.len() == self.user_dictionary_fields_num {
+        if self.is_simple_row(row) {
+            Ok(self.context(row)?.word_cost)
+        } else if row.len() == self.user_dictionary_fields_num {
             Ok(self.default_word_cost)
         } else {
             self.dictionary_parser.cost(row)
@@ -235,7 +326,9 @@ impl CSVParser for UserDictionaryParser {
     }
 
     fn left_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
-        if row.len() == self.user_dictionary_fields_num {
+        if self.is_simple_row(row) {
+            Ok(self.context(row)?.left_context_id)
+        } else if row.len() == self.user_dictionary_fields_num {
             Ok(self.default_left_context_id)
         } else {
             self.dictionary_parser.left_context_id(row)
@@ -243,7 +336,9 @@ impl CSVParser for UserDictionaryParser {
     }
 
     fn right_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
-        if row.len() == self.user_dictionary_fields_num {
+        if self.is_simple_row(row) {
+            Ok(self.context(row)?.right_context_id)
+        } else if row.len() == self.user_dictionary_fields_num {
             Ok(self.default_right_context_id)
         } else {
             self.dictionary_parser.right_context_id(row)
@@ -251,7 +346,9 @@ impl CSVParser for UserDictionaryParser {
     }
 
     fn details(&self, row: &StringRecord) -> Result<WordDetailsLine, CSVParseError> {
-        if row.len() == self.user_dictionary_fields_num {
+        if self.is_simple_row(row) {
+            self.simple_details(row)
+        } else if row.len() == self.user_dictionary_fields_num {
             self.user_dictionary_parser.details(row)
         } else {
             self.dictionary_parser.details(row)
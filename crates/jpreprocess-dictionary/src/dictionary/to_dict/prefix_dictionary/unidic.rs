@@ -0,0 +1,317 @@
+use std::str::FromStr;
+
+use csv::StringRecord;
+use jpreprocess_core::{
+    accent_rule::{AccentType, ChainRules, POSMatch},
+    pronunciation::Pronunciation,
+    word_details::WordDetails,
+    word_line::WordDetailsLine,
+};
+use lindera_dictionary::dictionary::schema::Schema;
+
+use crate::tokenizer::{
+    estimate::{estimate_accent, estimate_chain_rule},
+    unidic::map_unidic_pos,
+};
+
+use super::parser::{CSVParseError, CSVParser, DefaultParser};
+
+/// Parser for the lexicon CSV of UniDic (unidic-cwj and unidic-csj 3.x).
+///
+/// The POS hierarchy and conjugations are mapped onto the naist-jdic schema,
+/// the accent nucleus is read from `aType`, and the chain rule from `aConType`.
+/// Missing accent fields are estimated from POS and pronunciation.
+///
+/// `aModType`, the accent change of the following word (e.g. `M1@1` of prefixes), is ignored,
+/// as the word details of jpreprocess cannot express it.
+pub struct UnidicParser {
+    base: DefaultParser,
+}
+
+impl UnidicParser {
+    /// `base` reads surface, context ids and cost, and should use a UniDic schema
+    /// such as [`UnidicParser::default_schema`].
+    pub fn new(base: DefaultParser) -> Self {
+        Self { base }
+    }
+
+    /// Column layout of `lex.csv` in unidic-cwj and unidic-csj 3.x.
+    pub fn default_schema() -> Schema {
+        Schema::new(
+            [
+                "surface",
+                "left_context_id",
+                "right_context_id",
+                "cost",
+                "pos1",
+                "pos2",
+                "pos3",
+                "pos4",
+                "cType",
+                "cForm",
+                "lForm",
+                "lemma",
+                "orth",
+                "pron",
+                "orthBase",
+                "pronBase",
+                "goshu",
+                "iType",
+                "iForm",
+                "fType",
+                "fForm",
+                "iConType",
+                "fConType",
+                "type",
+                "kana",
+                "kanaBase",
+                "form",
+                "formBase",
+                "aType",
+                "aConType",
+                "aModType",
+                "lid",
+                "lemma_id",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        )
+    }
+
+    fn field(&self, row: &StringRecord, name: &str) -> String {
+        self.base
+            .get_field_value(row, name)
+            .unwrap_or_else(|| "*".to_string())
+    }
+}
+
+impl CSVParser for UnidicParser {
+    fn surface(&self, row: &StringRecord) -> Result<String, CSVParseError> {
+        self.base.surface(row)
+    }
+    fn left_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
+        self.base.left_context_id(row)
+    }
+    fn right_context_id(&self, row: &StringRecord) -> Result<u16, CSVParseError> {
+        self.base.right_context_id(row)
+    }
+    fn cost(&self, row: &StringRecord) -> Result<i16, CSVParseError> {
+        self.base.cost(row)
+    }
+
+    fn details(&self, row: &StringRecord) -> Result<WordDetailsLine, CSVParseError> {
+        let mapped = map_unidic_pos(
+            &["pos1", "pos2", "pos3", "pos4", "cType", "cForm"].map(|name| self.field(row, name)),
+        );
+        let kana = self.field(row, "kana");
+
+        // Symbols and alphabets have no pronunciation in UniDic.
+        let mut pron = Pronunciation::parse(&self.field(row, "pron"), 0)
+            .or_else(|_| Pronunciation::parse(&kana, 0))
+            .unwrap_or_default();
+        let accent = parse_accent_type(&self.field(row, "aType"))
+            .unwrap_or_else(|| estimate_accent(&mapped.pos, pron.mora_size()));
+        pron.set_accent(accent.min(pron.mora_size()));
+
+        let chain_rule = parse_accent_con_type(&self.field(row, "aConType"))
+            .unwrap_or_else(|| estimate_chain_rule(&mapped.pos));
+
+        let details = WordDetails {
+            pos: mapped.pos,
+            ctype: mapped.ctype,
+            cform: mapped.cform,
+            read: match kana.as_str() {
+                "*" => None,
+                kana => Some(kana.to_string()),
+            },
+            pron,
+            chain_rule,
+            chain_flag: None,
+        };
+        Ok(WordDetailsLine {
+            orig: self.field(row, "orthBase"),
+            ..WordDetailsLine::from(&details)
+        })
+    }
+}
+
+/// Reads the first accent nucleus of `aType` (e.g. `0`, `1,0`).
+fn parse_accent_type(a_type: &str) -> Option<usize> {
+    a_type.split(',').next()?.trim().parse().ok()
+}
+
+/// Converts `aConType` (e.g. `動詞%F2@0,形容詞%F2@-1`) into chain rules.
+///
+/// Rules that jpreprocess does not support (e.g. `F6`, `P4`, `特殊助動詞%...`) are dropped.
+fn parse_accent_con_type(a_con_type: &str) -> Option<ChainRules> {
+    let rules = a_con_type
+        .split(',')
+        .map(str::trim)
+        .filter(|rule| is_supported_rule(rule))
+        .collect::<Vec<_>>();
+    if rules.is_empty() {
+        None
+    } else {
+        Some(ChainRules::new(&rules.join("/")))
+    }
+}
+
+fn is_supported_rule(rule: &str) -> bool {
+    let rule = match rule.split_once('%') {
+        Some((pos, rule)) if POSMatch::from_str(pos).is_ok() => rule,
+        Some(_) => return false,
+        None => rule,
+    };
+    let accent_type = rule
+        .split_once('@')
+        .map_or(rule, |(accent_type, _)| accent_type);
+    !matches!(accent_type, "" | "*") && AccentType::from_str(accent_type).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::{
+        cform::CForm,
+        pos::{Doushi, Joshi, KakuJoshi, Meishi, POS},
+        word_entry::WordEntry,
+    };
+
+    use crate::dictionary::to_dict::prefix_dictionary::parser::DefaultParserOptions;
+
+    use super::*;
+
+    fn parser() -> UnidicParser {
+        UnidicParser::new(
+            DefaultParserOptions::default()
+                .schema(UnidicParser::default_schema())
+                .builder()
+                .unwrap(),
+        )
+    }
+
+    fn row(
+        surface: &str,
+        pos: [&str; 6],
+        orth_base: &str,
+        pron: &str,
+        a_type: &str,
+        a_con_type: &str,
+    ) -> StringRecord {
+        let mut fields = vec![surface, "10", "10", "3000"];
+        fields.extend(pos);
+        fields.extend([
+            "lForm", "lemma", surface, pron, orth_base, pron, "和", "*", "*", "*", "*", "*", "*",
+            "体", pron, pron, pron, pron, a_type, a_con_type, "*", "0", "0",
+        ]);
+        StringRecord::from(fields)
+    }
+
+    fn details(row: &StringRecord) -> WordDetails {
+        let WordEntry::Single(details) = parser().details(row).unwrap().try_into().unwrap() else {
+            panic!("expected single entry");
+        };
+        details
+    }
+
+    #[test]
+    fn noun() {
+        let row = row(
+            "東京",
+            ["名詞", "固有名詞", "地名", "一般", "*", "*"],
+            "東京",
+            "トーキョー",
+            "0",
+            "C1",
+        );
+        let parser = parser();
+        assert_eq!(parser.surface(&row).unwrap(), "東京");
+        assert_eq!(parser.cost(&row).unwrap(), 3000);
+
+        let line = parser.details(&row).unwrap();
+        assert_eq!(line.orig, "東京");
+        assert_eq!(line.acc_morasize, "0/4");
+
+        let details = details(&row);
+        assert!(matches!(details.pos, POS::Meishi(Meishi::KoyuMeishi(_))));
+        assert_eq!(details.pron.to_string(), "トーキョー");
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+    }
+
+    #[test]
+    fn verb() {
+        let details = details(&row(
+            "書い",
+            ["動詞", "一般", "*", "*", "五段-カ行", "連用形-イ音便"],
+            "書く",
+            "カイ",
+            "1",
+            "C1",
+        ));
+        assert_eq!(details.pos, POS::Doushi(Doushi::Jiritsu));
+        assert_eq!(details.cform, CForm::RenyouConjunctionTa);
+        assert_eq!(details.pron.accent(), 1);
+    }
+
+    #[test]
+    fn particle() {
+        let details = details(&row(
+            "が",
+            ["助詞", "格助詞", "*", "*", "*", "*"],
+            "が",
+            "ガ",
+            "*",
+            "動詞%F2@0,形容詞%F2@-1,名詞%F1,特殊助動詞%F2@0",
+        ));
+        assert_eq!(
+            details.pos,
+            POS::Joshi(Joshi::KakuJoshi(KakuJoshi::General))
+        );
+        assert_eq!(
+            details.chain_rule,
+            ChainRules::new("動詞%F2@0/形容詞%F2@-1/名詞%F1")
+        );
+    }
+
+    #[test]
+    fn estimated() {
+        let details = details(&row(
+            "かな",
+            ["名詞", "普通名詞", "一般", "*", "*", "*"],
+            "かな",
+            "カナ",
+            "*",
+            "*",
+        ));
+        assert_eq!(details.pron.accent(), 1);
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+    }
+
+    #[test]
+    fn accent_mod_type_ignored() {
+        let row = row(
+            "お",
+            ["接頭辞", "*", "*", "*", "*", "*"],
+            "御",
+            "オ",
+            "*",
+            "P2",
+        );
+        let schema = UnidicParser::default_schema();
+        let mut fields = row.iter().collect::<Vec<_>>();
+        fields[schema.get_field_index("aModType").unwrap()] = "M1@1";
+
+        assert_eq!(
+            parser().details(&StringRecord::from(fields)).unwrap(),
+            parser().details(&row).unwrap()
+        );
+    }
+
+    #[test]
+    fn accent_con_type() {
+        assert_eq!(parse_accent_type("1,0"), Some(1));
+        assert_eq!(parse_accent_type("*"), None);
+        assert!(parse_accent_con_type("*").is_none());
+        assert!(parse_accent_con_type("P4").is_none());
+        assert_eq!(parse_accent_con_type("C3"), Some(ChainRules::new("C3")));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+use csv::StringRecord;
+use jpreprocess_core::{accent_rule::ChainRules, word_entry::WordEntry, JPreprocessError};
+use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
+
+use super::{
+    prefix_dictionary::parser::{CSVParseError, CSVParser},
+    JPreprocessDictionaryBuilder,
+};
+
+/// A problem found in a row of a dictionary source.
+#[derive(Debug)]
+pub struct ValidationIssue {
+    /// The 1-based line number of the row.
+    pub line: u64,
+    pub surface: Option<String>,
+    pub kind: ValidationIssueKind,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.surface {
+            Some(surface) => write!(f, "line {} ({}): {}", self.line, surface, self.kind),
+            None => write!(f, "line {}: {}", self.line, self.kind),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationIssueKind {
+    #[error("Invalid CSV record: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("{0}")]
+    Field(#[from] CSVParseError),
+    #[error("{0}")]
+    Details(#[from] JPreprocessError),
+    #[error("Accent {accent} exceeds the mora size {mora_size}")]
+    AccentOutOfRange { accent: usize, mora_size: usize },
+    /// Homographs differing in context ids, POS or pronunciation are not duplicates.
+    #[error("The same word (surface, context ids, POS and pronunciation) is already defined at line {first_line}")]
+    DuplicateWord { first_line: u64 },
+}
+
+impl JPreprocessDictionaryBuilder {
+    /// Checks every row of a user dictionary source and reports all the problems found,
+    /// instead of failing on the first one like [`JPreprocessDictionaryBuilder::build_user_dictionary`].
+    ///
+    /// POS, conjugations, pronunciation, accent, chain rule and duplicate words are checked.
+    pub fn validate_user_dictionary(
+        &self,
+        input_file: &Path,
+    ) -> LinderaResult<Vec<ValidationIssue>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(self.metadata.flexible_csv)
+            .from_path(input_file)
+            .map_err(|err| {
+                LinderaErrorKind::Io
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Failed to open CSV file: {input_file:?}"))
+            })?;
+
+        let mut rows = Vec::new();
+        let mut issues = Vec::new();
+        for (i, result) in reader.records().enumerate() {
+            match result {
+                Ok(row) => rows.push((row.position().map_or(i as u64 + 1, |p| p.line()), row)),
+                Err(err) => issues.push(ValidationIssue {
+                    line: err.position().map_or(i as u64 + 1, |p| p.line()),
+                    surface: None,
+                    kind: err.into(),
+                }),
+            }
+        }
+
+        issues.extend(validate_rows(&self.user_dictionary_parser(), &rows));
+        issues.sort_by_key(|issue| issue.line);
+        Ok(issues)
+    }
+
+    /// Checks the rows of a user dictionary source, numbered from line 1.
+    pub fn validate_user_dict_from_data(&self, data: Vec<Vec<&str>>) -> Vec<ValidationIssue> {
+        let rows = data
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| (i as u64 + 1, StringRecord::from_iter(row)))
+            .collect::<Vec<_>>();
+
+        validate_rows(&self.user_dictionary_parser(), &rows)
+    }
+}
+
+fn validate_rows<P: CSVParser>(parser: &P, rows: &[(u64, StringRecord)]) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut first_lines: HashMap<Vec<String>, u64> = HashMap::new();
+
+    for (line, row) in rows {
+        let surface = parser.surface(row).ok();
+
+        let mut kinds = validate_row(parser, row);
+        if let Some(key) = duplicate_key(parser, row) {
+            let first_line = *first_lines.entry(key).or_insert(*line);
+            if first_line != *line {
+                kinds.push(ValidationIssueKind::DuplicateWord { first_line });
+            }
+        }
+
+        issues.extend(kinds.into_iter().map(|kind| ValidationIssue {
+            line: *line,
+            surface: surface.clone(),
+            kind,
+        }));
+    }
+
+    issues
+}
+
+/// The fields identifying a word: surface, context ids, POS and pronunciation.
+fn duplicate_key<P: CSVParser>(parser: &P, row: &StringRecord) -> Option<Vec<String>> {
+    let details = parser.details(row).ok()?;
+    Some(vec![
+        parser.surface(row).ok()?,
+        parser.left_context_id(row).ok()?.to_string(),
+        parser.right_context_id(row).ok()?.to_string(),
+        details.pos,
+        details.pos_group1,
+        details.pos_group2,
+        details.pos_group3,
+        details.pron,
+    ])
+}
+
+fn validate_row<P: CSVParser>(parser: &P, row: &StringRecord) -> Vec<ValidationIssueKind> {
+    let mut kinds = Vec::new();
+
+    // The fields of a simplified row all come from its POS, so report each problem once.
+    let mut field_errors: Vec<String> = Vec::new();
+    let mut push_field_error = |kinds: &mut Vec<ValidationIssueKind>, err: CSVParseError| {
+        let message = err.to_string();
+        if !field_errors.contains(&message) {
+            field_errors.push(message);
+            kinds.push(err.into());
+        }
+    };
+
+    for result in [
+        parser.surface(row).map(|_| ()),
+        parser.left_context_id(row).map(|_| ()),
+        parser.right_context_id(row).map(|_| ()),
+        parser.cost(row).map(|_| ()),
+    ] {
+        if let Err(err) = result {
+            push_field_error(&mut kinds, err);
+        }
+    }
+
+    let mut details = match parser.details(row) {
+        Ok(details) => details,
+        Err(err) => {
+            push_field_error(&mut kinds, err);
+            return kinds;
+        }
+    };
+
+    // `ChainRules::new` skips invalid rules with a warning, so check them beforehand.
+    if let Err(err) = ChainRules::parse(&details.chain_rule) {
+        kinds.push(err.into());
+        details.chain_rule = "*".to_string();
+    }
+
+    match WordEntry::try_from(details) {
+        Ok(entry) => {
+            for (_, details) in entry.get_with_string("") {
+                let (accent, mora_size) = (details.pron.accent(), details.pron.mora_size());
+                if accent > mora_size {
+                    kinds.push(ValidationIssueKind::AccentOutOfRange { accent, mora_size });
+                }
+            }
+        }
+        Err(err) => kinds.push(err.into()),
+    }
+
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::UserRowFormat, *};
+
+    fn row<'a>(
+        surface: &'a str,
+        pos: &'a str,
+        pron: &'a str,
+        acc: &'a str,
+        chain_rule: &'a str,
+    ) -> Vec<&'a str> {
+        vec![
+            surface,
+            "1285",
+            "1285",
+            "-3000",
+            pos,
+            "固有名詞",
+            "一般",
+            "*",
+            "*",
+            "*",
+            "*",
+            pron,
+            pron,
+            acc,
+            chain_rule,
+            "*",
+        ]
+    }
+
+    #[test]
+    fn valid() {
+        let issues = JPreprocessDictionaryBuilder::default().validate_user_dict_from_data(vec![
+            row("東京", "名詞", "トウキョウ", "0/4", "C1"),
+            vec!["京都", "キョウト", "キョート"],
+            // Homographs
+            row("東京", "名詞", "トーキョー", "0/4", "C1"),
+            row("京都", "名詞", "キョウト", "1/3", "C1"),
+        ]);
+        assert!(issues.is_empty(), "{issues:?}");
+    }
+
+    #[test]
+    fn invalid() {
+        let issues = JPreprocessDictionaryBuilder::default().validate_user_dict_from_data(vec![
+            row("東京", "名詞", "トウキョウ", "0/4", "C1"),
+            row("大阪", "めいし", "オオサカ", "0/4", "C1"),
+            row("京都", "名詞", "キョウト", "1/2", "C1"),
+            row("奈良", "名詞", "ナラ", "5/2", "名詞%F9"),
+            row("東京", "名詞", "トウキョウ", "0/4", "C1"),
+            vec!["神戸", "1285", "x", "-3000"],
+        ]);
+
+        let summary = issues
+            .iter()
+            .map(|issue| (issue.line, &issue.kind))
+            .collect::<Vec<_>>();
+        assert!(
+            matches!(
+                summary.as_slice(),
+                [
+                    (
+                        2,
+                        ValidationIssueKind::Details(JPreprocessError::PartOfSpeechParseError(_))
+                    ),
+                    (
+                        3,
+                        ValidationIssueKind::Details(JPreprocessError::PronunciationParseError(_))
+                    ),
+                    (
+                        4,
+                        ValidationIssueKind::Details(JPreprocessError::AccentRuleParseError(_))
+                    ),
+                    (
+                        4,
+                        ValidationIssueKind::AccentOutOfRange {
+                            accent: 5,
+                            mora_size: 2
+                        }
+                    ),
+                    (5, ValidationIssueKind::DuplicateWord { first_line: 1 }),
+                    (6, ValidationIssueKind::Field(_)),
+                    ..
+                ]
+            ),
+            "{summary:?}"
+        );
+        assert_eq!(
+            issues[4].to_string(),
+            "line 5 (東京): The same word (surface, context ids, POS and pronunciation) is already defined at line 1"
+        );
+    }
+
+    #[test]
+    fn simplified_without_context() {
+        let issues = JPreprocessDictionaryBuilder::default()
+            .with_user_row_format(UserRowFormat::Simplified)
+            .validate_user_dict_from_data(vec![vec!["東京", "トーキョー", "0"]]);
+        assert!(
+            matches!(
+                issues.as_slice(),
+                [ValidationIssue {
+                    line: 1,
+                    kind: ValidationIssueKind::Field(CSVParseError::NoPresetContext(_)),
+                    ..
+                }]
+            ),
+            "{issues:?}"
+        );
+    }
+}
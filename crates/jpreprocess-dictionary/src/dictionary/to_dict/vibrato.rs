@@ -0,0 +1,280 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use csv::StringRecord;
+use encoding_rs::{Encoding, UTF_8};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use jpreprocess_core::{word_entry::WordEntry, word_line::WordDetailsLine};
+use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
+
+use super::{
+    prefix_dictionary::{
+        parser::{CSVParseError, CSVParser},
+        CSVReaderOptions,
+    },
+    JPreprocessDictionaryBuilder,
+};
+
+impl JPreprocessDictionaryBuilder {
+    /// Builds a Vibrato system dictionary from the source directory and writes it to `output_file`.
+    ///
+    /// The directory must contain the lexicon CSV files, `matrix.def`, `char.def` and `unk.def`.
+    pub fn build_vibrato_dictionary(
+        &self,
+        input_dir: &Path,
+        output_file: &Path,
+    ) -> LinderaResult<()> {
+        let dict = self.build_vibrato_system_dictionary(input_dir)?;
+
+        let file = File::create(output_file).map_err(|err| {
+            LinderaErrorKind::Io
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to create {output_file:?}"))
+        })?;
+        dict.write(file).map_err(|err| {
+            LinderaErrorKind::Serialize
+                .with_error(anyhow::anyhow!(err))
+                .add_context("Failed to write Vibrato dictionary")
+        })?;
+
+        Ok(())
+    }
+
+    /// Builds a Vibrato system dictionary from the source directory.
+    ///
+    /// Every entry is validated as a jpreprocess word, and its features are stored
+    /// as the twelve jpreprocess detail fields.
+    pub fn build_vibrato_system_dictionary(
+        &self,
+        input_dir: &Path,
+    ) -> LinderaResult<vibrato::Dictionary> {
+        let reader = CSVReaderOptions::default()
+            .flexible_csv(self.metadata.flexible_csv)
+            .encoding(self.metadata.encoding.clone())
+            .normalize_details(self.metadata.normalize_details)
+            .builder()
+            .unwrap();
+        let rows = reader.load_csv_data(input_dir)?;
+
+        let lexicon = vibrato_lexicon(&self.system_dictionary_parser(), &rows)?;
+
+        vibrato::SystemDictionaryBuilder::from_readers(
+            Cursor::new(lexicon),
+            self.open_definition(&input_dir.join("matrix.def"))?,
+            self.open_definition(&input_dir.join("char.def"))?,
+            self.open_definition(&input_dir.join("unk.def"))?,
+        )
+        .map_err(|err| {
+            LinderaErrorKind::Build
+                .with_error(anyhow::anyhow!(err))
+                .add_context("Failed to build Vibrato dictionary")
+        })
+    }
+
+    /// Converts a lindera-style user dictionary CSV into a Vibrato user lexicon,
+    /// which can be passed to `vibrato::Dictionary::reset_user_lexicon_from_reader`.
+    pub fn build_vibrato_user_lexicon(
+        &self,
+        input_file: &Path,
+        output_file: &Path,
+    ) -> LinderaResult<()> {
+        let reader = CSVReaderOptions::default()
+            .flexible_csv(self.metadata.flexible_csv)
+            .builder()
+            .unwrap();
+        let rows = reader.read_csv_files(&[input_file.to_path_buf()])?;
+
+        let lexicon = vibrato_lexicon(&self.user_dictionary_parser(), &rows)?;
+        std::fs::write(output_file, lexicon).map_err(|err| {
+            LinderaErrorKind::Io
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to write {output_file:?}"))
+        })
+    }
+
+    /// Same as [`build_vibrato_user_lexicon`](Self::build_vibrato_user_lexicon),
+    /// but takes the rows directly and returns the lexicon.
+    pub fn build_vibrato_user_lexicon_from_data(
+        &self,
+        data: Vec<Vec<&str>>,
+    ) -> LinderaResult<Vec<u8>> {
+        let rows = data
+            .into_iter()
+            .map(StringRecord::from_iter)
+            .collect::<Vec<_>>();
+
+        vibrato_lexicon(&self.user_dictionary_parser(), &rows)
+    }
+
+    fn open_definition(&self, path: &Path) -> LinderaResult<Box<dyn Read>> {
+        let file = File::open(path).map_err(|err| {
+            LinderaErrorKind::Io
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to open {path:?}"))
+        })?;
+
+        let encoding = if self.metadata.encoding.is_empty() {
+            UTF_8
+        } else {
+            Encoding::for_label_no_replacement(self.metadata.encoding.as_bytes()).ok_or_else(
+                || {
+                    LinderaErrorKind::Args.with_error(anyhow::anyhow!(
+                        "Invalid encoding: {}",
+                        self.metadata.encoding
+                    ))
+                },
+            )?
+        };
+
+        if encoding == UTF_8 {
+            Ok(Box::new(file))
+        } else {
+            Ok(Box::new(
+                DecodeReaderBytesBuilder::new()
+                    .encoding(Some(encoding))
+                    .build(file),
+            ))
+        }
+    }
+}
+
+/// Writes the rows as a Vibrato lexicon (`surface,left_id,right_id,cost,features...`).
+fn vibrato_lexicon<P: CSVParser>(parser: &P, rows: &[StringRecord]) -> LinderaResult<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    for row in rows {
+        let parse_error = |err: CSVParseError| {
+            LinderaErrorKind::Parse
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to parse CSV row: {row:?}"))
+        };
+
+        let surface = parser.surface(row).map_err(parse_error)?;
+        let left_id = parser.left_context_id(row).map_err(parse_error)?;
+        let right_id = parser.right_context_id(row).map_err(parse_error)?;
+        let cost = parser.cost(row).map_err(parse_error)?;
+
+        let entry =
+            WordEntry::try_from(parser.details(row).map_err(parse_error)?).map_err(|err| {
+                LinderaErrorKind::Parse
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Invalid word details: {row:?}"))
+            })?;
+        let line = WordDetailsLine::from(&entry);
+        let orig = line.orig.clone();
+
+        let mut record = vec![
+            surface,
+            left_id.to_string(),
+            right_id.to_string(),
+            cost.to_string(),
+        ];
+        record.extend(line.to_str_vec(orig));
+
+        writer.write_record(&record).map_err(|err| {
+            LinderaErrorKind::Serialize
+                .with_error(anyhow::anyhow!(err))
+                .add_context("Failed to write Vibrato lexicon")
+        })?;
+    }
+
+    writer.into_inner().map_err(|err| {
+        LinderaErrorKind::Serialize
+            .with_error(anyhow::anyhow!(err.into_error()))
+            .add_context("Failed to write Vibrato lexicon")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::token::{Token, TokenOrigin, Tokenizer};
+    use lindera_dictionary::dictionary::metadata::Metadata;
+
+    use super::*;
+
+    fn row(surface: &str, pos: &str, pron: &str, acc: &str) -> Vec<String> {
+        [
+            surface, "1", "1", "100", pos, "一般", "*", "*", "*", "*", surface, pron, pron, acc,
+            "*", "*",
+        ]
+        .map(str::to_string)
+        .to_vec()
+    }
+
+    #[test]
+    fn vibrato_dictionary() {
+        let builder = JPreprocessDictionaryBuilder::new(Metadata {
+            default_left_context_id: 1,
+            default_right_context_id: 1,
+            ..JPreprocessDictionaryBuilder::default_metadata()
+        });
+        let parser = builder.system_dictionary_parser();
+
+        let rows = [
+            row("日本", "名詞", "ニホン", "2/3"),
+            row("語", "名詞", "ゴ", "1/1"),
+        ]
+        .iter()
+        .map(StringRecord::from_iter)
+        .collect::<Vec<_>>();
+        let lexicon = vibrato_lexicon(&parser, &rows).unwrap();
+
+        let dict = vibrato::SystemDictionaryBuilder::from_readers(
+            Cursor::new(lexicon),
+            "2 2\n0 0 0\n0 1 0\n1 0 0\n1 1 0\n".as_bytes(),
+            "DEFAULT 0 1 0\n".as_bytes(),
+            "DEFAULT,0,0,1000,名詞,一般,*,*,*,*,*\n".as_bytes(),
+        )
+        .unwrap();
+
+        let user = builder
+            .build_vibrato_user_lexicon_from_data(vec![vec!["を", "ヲ", "オ"]])
+            .unwrap();
+        let dict = dict
+            .reset_user_lexicon_from_reader(Some(user.as_slice()))
+            .unwrap();
+        let tokenizer = vibrato::Tokenizer::new(dict);
+
+        let tokens = Tokenizer::tokenize(&tokenizer, "日本語を。").unwrap();
+        let mut tokens = tokens.into_iter();
+
+        let mut token = tokens.next().unwrap();
+        assert_eq!(token.metadata().unwrap().origin, TokenOrigin::System);
+        let (surface, entry) = token.fetch().unwrap();
+        assert_eq!(surface, "日本");
+        assert_eq!(
+            entry,
+            WordEntry::load(&[
+                "名詞",
+                "一般",
+                "*",
+                "*",
+                "*",
+                "*",
+                "日本",
+                "ニホン",
+                "ニホン",
+                "2/3",
+                "*",
+                "*"
+            ])
+            .unwrap()
+        );
+
+        let token = tokens.next().unwrap();
+        assert_eq!(token.metadata().unwrap().origin, TokenOrigin::System);
+
+        let token = tokens.next().unwrap();
+        assert_eq!(token.metadata().unwrap().origin, TokenOrigin::User);
+
+        let mut token = tokens.next().unwrap();
+        assert_eq!(token.metadata().unwrap().origin, TokenOrigin::Unknown);
+        assert_eq!(token.fetch().unwrap(), ("。", WordEntry::default()));
+    }
+}
@@ -0,0 +1,572 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use jpreprocess_core::{
+    pos::POS, pronunciation::Pronunciation, word_details::WordDetails, word_line::WordDetailsLine,
+    JPreprocessResult,
+};
+use lindera_dictionary::{
+    builder::user_dictionary::build_user_dictionary,
+    dictionary::{prefix_dictionary::PrefixDictionary, UserDictionary},
+    error::LinderaErrorKind,
+    loader::user_dictionary::UserDictionaryLoader,
+    viterbi::{LexType, WordEntry, WordId},
+    LinderaResult,
+};
+
+use crate::{
+    tokenizer::{
+        estimate::{estimate_accent, estimate_chain_rule},
+        identify_dictionary::DictionaryIdent,
+    },
+    word_data::{encode_preamble, get_identifier, get_surfaces, get_word_data},
+};
+
+use super::{
+    export::{export_prefix_dictionary, word_details, write_csv, ExportedWord},
+    to_dict::{build_user_prefix_dictionary, JPreprocessDictionaryBuilder},
+    word_encoding::WordFormat,
+};
+
+/// Frequently used POS for user dictionary words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum PosPreset {
+    /// 名詞,固有名詞,一般
+    #[default]
+    ProperNoun,
+    /// 名詞,一般
+    CommonNoun,
+    /// 名詞,サ変接続
+    SahenNoun,
+}
+
+impl PosPreset {
+    pub const ALL: [Self; 3] = [Self::ProperNoun, Self::CommonNoun, Self::SahenNoun];
+
+    /// The four POS fields of naist-jdic.
+    pub fn pos_fields(&self) -> [&'static str; 4] {
+        match self {
+            Self::ProperNoun => ["名詞", "固有名詞", "一般", "*"],
+            Self::CommonNoun => ["名詞", "一般", "*", "*"],
+            Self::SahenNoun => ["名詞", "サ変接続", "*", "*"],
+        }
+    }
+
+    /// Creates the word details from the katakana pronunciation.
+    ///
+    /// The accent nucleus is estimated from the POS if not given,
+    /// and the chain rule is always estimated.
+    pub fn details(&self, pron: &str, accent: Option<usize>) -> JPreprocessResult<WordDetailsLine> {
+        let [g0, g1, g2, g3] = self.pos_fields();
+        let pos = POS::from_strs(g0, g1, g2, g3)?;
+
+        let mut pronunciation = Pronunciation::parse(pron, 0)?;
+        pronunciation
+            .set_accent(accent.unwrap_or_else(|| estimate_accent(&pos, pronunciation.mora_size())));
+
+        let details = WordDetails {
+            chain_rule: estimate_chain_rule(&pos),
+            pos,
+            read: Some(pron.to_string()),
+            pron: pronunciation,
+            ..Default::default()
+        };
+        Ok(WordDetailsLine::from(&details))
+    }
+}
+
+impl FromStr for PosPreset {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "固有名詞" | "proper-noun" => Ok(Self::ProperNoun),
+            "一般名詞" | "普通名詞" | "common-noun" => Ok(Self::CommonNoun),
+            "サ変" | "サ変名詞" | "sahen-noun" => Ok(Self::SahenNoun),
+            _ => Err(format!("Unknown POS preset {s}")),
+        }
+    }
+}
+
+/// Context ids and cost given to new words of a POS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordContext {
+    pub left_context_id: u16,
+    pub right_context_id: u16,
+    pub word_cost: i16,
+}
+
+/// Computes the context of each preset from the words of a system dictionary.
+///
+/// The context ids are the most common ones among the words of the POS,
+/// and the cost is the median cost of the words with those ids, so that a new word is
+/// as likely as a typical word of the POS.
+pub fn preset_contexts(dict: &PrefixDictionary) -> LinderaResult<HashMap<PosPreset, WordContext>> {
+    let ident = DictionaryIdent::from_idx_data(&dict.words_idx_data, &dict.words_data)
+        .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?;
+
+    let mut costs: HashMap<(PosPreset, u16, u16), Vec<i16>> = HashMap::new();
+    for data in dict.vals_data.chunks_exact(WordEntry::SERIALIZED_LEN) {
+        let entry = WordEntry::deserialize(data, dict.is_system);
+        let details = word_details(dict, &ident, entry.word_id.id)?;
+        let Some(preset) = PosPreset::ALL.into_iter().find(|preset| {
+            details.len() >= 4 && preset.pos_fields().iter().eq(details[..4].iter())
+        }) else {
+            continue;
+        };
+
+        costs
+            .entry((preset, entry.left_id, entry.right_id))
+            .or_default()
+            .push(entry.word_cost);
+    }
+
+    let mut contexts: HashMap<PosPreset, (usize, WordContext)> = HashMap::new();
+    for ((preset, left_context_id, right_context_id), mut costs) in costs {
+        let count = costs.len();
+        let (_, &mut word_cost, _) = costs.select_nth_unstable(count / 2);
+        let context = WordContext {
+            left_context_id,
+            right_context_id,
+            word_cost,
+        };
+        match contexts.get(&preset) {
+            Some((best, best_context))
+                if (*best, best_context.left_context_id) >= (count, left_context_id) => {}
+            _ => {
+                contexts.insert(preset, (count, context));
+            }
+        }
+    }
+
+    Ok(contexts
+        .into_iter()
+        .map(|(preset, (_, context))| (preset, context))
+        .collect())
+}
+
+/// Edits a user dictionary word by word.
+///
+/// The words are kept as rows of the naist-jdic schema, and can be read from and
+/// written to a compiled user dictionary (`.bin`) or a source CSV.
+#[derive(Clone)]
+pub struct UserDictionaryEditor {
+    builder: JPreprocessDictionaryBuilder,
+    words: Vec<ExportedWord>,
+}
+
+impl UserDictionaryEditor {
+    pub fn new(builder: JPreprocessDictionaryBuilder) -> Self {
+        Self {
+            builder,
+            words: vec![],
+        }
+    }
+
+    /// Uses the context ids and costs of the system dictionary for new words.
+    ///
+    /// Without this, words can only be added with [`UserDictionaryEditor::add_word`].
+    pub fn with_system_dictionary(self, dict: &PrefixDictionary) -> LinderaResult<Self> {
+        Ok(Self {
+            builder: self.builder.with_preset_contexts(preset_contexts(dict)?),
+            ..self
+        })
+    }
+
+    /// Loads the words of a compiled user dictionary.
+    pub fn read_bin(self, path: &Path) -> LinderaResult<Self> {
+        let user_dict = UserDictionaryLoader::load_from_bin(path)?;
+        Ok(Self {
+            words: export_prefix_dictionary(&user_dict.dict)?,
+            ..self
+        })
+    }
+
+    /// Loads the words of a user dictionary source CSV.
+    pub fn read_csv(self, path: &Path) -> LinderaResult<Self> {
+        let user_dict = self.builder.build_user_dict(path)?;
+        Ok(Self {
+            words: export_prefix_dictionary(&user_dict.dict)?,
+            ..self
+        })
+    }
+
+    pub fn words(&self) -> &[ExportedWord] {
+        &self.words
+    }
+
+    /// The context ids and cost given to a new word of the preset,
+    /// if the system dictionary has words of the preset.
+    pub fn context(&self, preset: PosPreset) -> Option<WordContext> {
+        self.builder.preset_contexts.get(&preset).copied()
+    }
+
+    /// Adds a word, replacing the words with the same surface and POS.
+    ///
+    /// The context ids are those of [`UserDictionaryEditor::context`], and `cost` defaults to its cost.
+    /// Fails if the editor has no context for the preset.
+    pub fn add(
+        &mut self,
+        surface: &str,
+        pron: &str,
+        accent: Option<usize>,
+        preset: PosPreset,
+        cost: Option<i16>,
+    ) -> LinderaResult<()> {
+        let details = preset.details(pron, accent).map_err(|err| {
+            LinderaErrorKind::Parse
+                .with_error(err)
+                .add_context(format!("Invalid word {surface}"))
+        })?;
+        let context = self.context(preset).ok_or_else(|| {
+            LinderaErrorKind::Args
+                .with_error(anyhow::anyhow!(
+                    "No context ids for {preset:?}; use the editor with a system dictionary"
+                ))
+                .add_context(format!("Invalid word {surface}"))
+        })?;
+
+        self.add_word(ExportedWord {
+            word_id: 0,
+            surface: surface.to_string(),
+            left_context_id: context.left_context_id,
+            right_context_id: context.right_context_id,
+            word_cost: cost.unwrap_or(context.word_cost),
+            details: details.to_str_vec(details.orig.clone()).to_vec(),
+        })
+    }
+
+    /// Adds a word, replacing the words with the same surface and POS.
+    ///
+    /// The word is validated like [`JPreprocessDictionaryBuilder::validate_user_dictionary`].
+    pub fn add_word(&mut self, word: ExportedWord) -> LinderaResult<()> {
+        let record = word.to_record();
+        let issues = self
+            .builder
+            .validate_user_dict_from_data(vec![record.iter().map(String::as_str).collect()]);
+        if let Some(issue) = issues.first() {
+            return Err(LinderaErrorKind::Content
+                .with_error(anyhow::anyhow!("{}", issue.kind))
+                .add_context(format!("Invalid word {}", word.surface)));
+        }
+
+        self.words.retain(|other| {
+            other.surface != word.surface || other.details.get(..4) != word.details.get(..4)
+        });
+        self.words.push(word);
+        Ok(())
+    }
+
+    /// Removes the words with the surface, and returns the number of removed words.
+    pub fn remove(&mut self, surface: &str) -> usize {
+        let len = self.words.len();
+        self.words.retain(|word| word.surface != surface);
+        len - self.words.len()
+    }
+
+    pub fn build(&self) -> LinderaResult<UserDictionary> {
+        let records = self
+            .words
+            .iter()
+            .map(ExportedWord::to_record)
+            .collect::<Vec<_>>();
+        self.builder.build_user_dict_from_data(
+            records
+                .iter()
+                .map(|record| record.iter().map(String::as_str).collect())
+                .collect(),
+        )
+    }
+
+    /// Writes the words as a compiled user dictionary.
+    pub fn write_bin(&self, path: &Path) -> LinderaResult<()> {
+        build_user_dictionary(self.build()?, path)
+    }
+
+    /// Writes the words as a user dictionary source CSV.
+    pub fn write_csv(&self, path: &Path) -> LinderaResult<()> {
+        let file = fs::File::create(path).map_err(|err| {
+            LinderaErrorKind::Io
+                .with_error(anyhow::anyhow!(err))
+                .add_context(format!("Failed to create {path:?}"))
+        })?;
+        write_csv(&self.words, file)
+    }
+}
+
+/// Merges user dictionaries into one, ordered by priority from the highest.
+///
+/// The words of a dictionary are dropped if a dictionary of higher priority
+/// has words with the same surface.
+/// The words are copied as they are encoded, so the dictionaries must have the same word encoding,
+/// and record the surfaces of their words like those built by this JPreprocess.
+pub fn stack_user_dictionaries(dictionaries: &[&UserDictionary]) -> LinderaResult<UserDictionary> {
+    let invalid = |message: String| LinderaErrorKind::Args.with_error(anyhow::anyhow!(message));
+
+    let mut identifier: Option<&[u8]> = None;
+    let mut higher_surfaces = HashSet::new();
+    let mut word_entry_map: BTreeMap<String, Vec<WordEntry>> = BTreeMap::new();
+    let mut surfaces = Vec::new();
+    let mut words_data: Vec<u8> = Vec::new();
+    let mut words_idx = Vec::new();
+
+    for (i, dictionary) in dictionaries.iter().enumerate() {
+        let dict = &dictionary.dict;
+        let dict_identifier = get_identifier(&dict.words_idx_data, &dict.words_data)
+            .ok_or_else(|| invalid(format!("User dictionary {i} has no words")))?;
+        match identifier {
+            None => identifier = Some(dict_identifier),
+            Some(identifier) if word_encoding(identifier)? != word_encoding(dict_identifier)? => {
+                return Err(invalid(format!(
+                    "User dictionary {i} has a word encoding different from the first one"
+                )));
+            }
+            Some(_) => {}
+        }
+        let dict_surfaces = get_surfaces(&dict.words_idx_data, &dict.words_data).ok_or_else(|| {
+            invalid(format!(
+                "User dictionary {i} does not record the surfaces of its words. Rebuild it from the source."
+            ))
+        })?;
+
+        let mut entries = dict
+            .vals_data
+            .chunks_exact(WordEntry::SERIALIZED_LEN)
+            .map(|data| WordEntry::deserialize(data, dict.is_system))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.word_id.id);
+
+        for entry in entries {
+            let word_id = entry.word_id.id as usize;
+            let (Some(&surface), Some(data)) = (
+                dict_surfaces.get(word_id),
+                get_word_data(&dict.words_idx_data, &dict.words_data, Some(word_id)),
+            ) else {
+                return Err(invalid(format!(
+                    "Word {word_id} of user dictionary {i} is out of range"
+                )));
+            };
+            if higher_surfaces.contains(surface) {
+                continue;
+            }
+
+            word_entry_map
+                .entry(surface.to_string())
+                .or_default()
+                .push(WordEntry {
+                    word_id: WordId::new(LexType::System, surfaces.len() as u32),
+                    ..entry
+                });
+            surfaces.push(surface);
+            words_idx.extend((words_data.len() as u32).to_le_bytes());
+            words_data.extend(data);
+        }
+        higher_surfaces.extend(dict_surfaces);
+    }
+
+    let mut words = encode_preamble(
+        std::str::from_utf8(identifier.unwrap_or_default())
+            .map_err(|err| LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(err)))?,
+        surfaces,
+    );
+    let preamble_len = words.len() as u32;
+    words.extend(words_data);
+    let words_idx = words_idx
+        .chunks_exact(4)
+        .flat_map(|offset| {
+            (u32::from_le_bytes(offset.try_into().unwrap()) + preamble_len).to_le_bytes()
+        })
+        .collect();
+
+    Ok(UserDictionary {
+        dict: build_user_prefix_dictionary(&word_entry_map, words_idx, words)?,
+    })
+}
+
+/// The word encoding and format of a dictionary identifier, which must match to merge the words.
+fn word_encoding(identifier: &[u8]) -> LinderaResult<Option<u32>> {
+    if identifier.is_empty() {
+        return Ok(None);
+    }
+    let format = std::str::from_utf8(identifier)
+        .map_err(|err| LinderaErrorKind::Deserialize.with_error(anyhow::anyhow!(err)))
+        .and_then(|identifier| {
+            WordFormat::from_identifier(identifier)
+                .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))
+        })?;
+    Ok(Some(format.version()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dictionary::{
+            convert::{convert_prefix_dictionary, WordEncoding},
+            to_dict::UserRowFormat,
+        },
+        tokenizer::estimate::DictionarySchema,
+    };
+
+    use super::*;
+
+    /// A builder with the same context for every preset.
+    fn builder() -> JPreprocessDictionaryBuilder {
+        JPreprocessDictionaryBuilder::default()
+            .with_user_row_format(UserRowFormat::Simplified)
+            .with_preset_contexts(
+                PosPreset::ALL
+                    .into_iter()
+                    .map(|preset| {
+                        let context = WordContext {
+                            left_context_id: 1288,
+                            right_context_id: 1288,
+                            word_cost: -10000,
+                        };
+                        (preset, context)
+                    })
+                    .collect(),
+            )
+    }
+
+    #[test]
+    fn preset() {
+        let details = PosPreset::ProperNoun.details("トーキョー", None).unwrap();
+        assert_eq!(details.pos_group1, "固有名詞");
+        assert_eq!(details.acc_morasize, "0/4");
+        assert_eq!(details.chain_rule, "C1");
+
+        let details = PosPreset::SahenNoun.details("ケンサク", Some(0)).unwrap();
+        assert_eq!(details.acc_morasize, "0/4");
+        assert_eq!(details.chain_rule, "C2");
+
+        assert_eq!(PosPreset::from_str("サ変"), Ok(PosPreset::SahenNoun));
+    }
+
+    #[test]
+    fn edit() {
+        assert!(
+            UserDictionaryEditor::new(JPreprocessDictionaryBuilder::default())
+                .add("東京", "トーキョー", Some(0), PosPreset::ProperNoun, None)
+                .is_err()
+        );
+
+        let mut editor = UserDictionaryEditor::new(builder());
+        editor
+            .add("東京", "トーキョー", Some(0), PosPreset::ProperNoun, None)
+            .unwrap();
+        editor
+            .add("京都", "キョート", Some(1), PosPreset::ProperNoun, None)
+            .unwrap();
+        editor
+            .add(
+                "東京",
+                "トーキョー",
+                Some(1),
+                PosPreset::ProperNoun,
+                Some(-100),
+            )
+            .unwrap();
+        editor
+            .add("東京", "トーキョー", Some(0), PosPreset::CommonNoun, None)
+            .unwrap();
+        assert!(editor
+            .add("大阪", "オーサカ", Some(9), PosPreset::ProperNoun, None)
+            .is_err());
+
+        assert_eq!(editor.words().len(), 3);
+        assert_eq!(editor.words()[1].word_cost, -100);
+        assert_eq!(editor.words()[1].details[9], "1/4");
+
+        assert_eq!(editor.remove("東京"), 2);
+        let user_dict = editor.build().unwrap();
+        assert_eq!(user_dict.dict.find_surface("京都").len(), 1);
+        assert!(user_dict.dict.find_surface("東京").is_empty());
+    }
+
+    #[test]
+    fn contexts() {
+        let mut editor = UserDictionaryEditor::new(JPreprocessDictionaryBuilder::default());
+        for (surface, pron, cost) in [
+            ("東京", "トーキョー", 10),
+            ("京都", "キョート", 20),
+            ("大阪", "オーサカ", 60),
+        ] {
+            editor
+                .add_word(ExportedWord {
+                    word_id: 0,
+                    surface: surface.to_string(),
+                    left_context_id: 1348,
+                    right_context_id: 1348,
+                    word_cost: cost,
+                    details: PosPreset::ProperNoun
+                        .details(pron, None)
+                        .unwrap()
+                        .to_str_vec("*".to_string())
+                        .to_vec(),
+                })
+                .unwrap();
+        }
+        let dict = editor.build().unwrap().dict;
+
+        let contexts = preset_contexts(&dict).unwrap();
+        assert_eq!(
+            contexts.get(&PosPreset::ProperNoun),
+            Some(&WordContext {
+                left_context_id: 1348,
+                right_context_id: 1348,
+                word_cost: 20,
+            })
+        );
+        assert!(!contexts.contains_key(&PosPreset::CommonNoun));
+    }
+
+    #[test]
+    fn stack() {
+        let builder = builder();
+        let high = builder
+            .build_user_dict_from_data(vec![vec!["東京", "トーキョー", "0"]])
+            .unwrap();
+        let low = builder
+            .build_user_dict_from_data(vec![
+                vec!["東京", "トウキョウ", "0"],
+                vec!["京都", "キョート", "1"],
+            ])
+            .unwrap();
+
+        let stacked = stack_user_dictionaries(&[&high, &low]).unwrap();
+        let words = export_prefix_dictionary(&stacked.dict).unwrap();
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].surface, "東京");
+        assert_eq!(words[0].details[8], "トーキョー");
+        assert_eq!(words[1].surface, "京都");
+
+        // The words are copied without decoding them.
+        let word = |dict: &PrefixDictionary, word_id| {
+            get_word_data(&dict.words_idx_data, &dict.words_data, Some(word_id))
+                .unwrap()
+                .to_vec()
+        };
+        assert_eq!(word(&stacked.dict, 0), word(&high.dict, 0));
+        assert_eq!(word(&stacked.dict, 1), word(&low.dict, 1));
+
+        // The word encoding is kept, and cannot be mixed.
+        let lindera = UserDictionary {
+            dict: convert_prefix_dictionary(
+                &low.dict,
+                WordEncoding::Lindera,
+                DictionarySchema::default(),
+            )
+            .unwrap(),
+        };
+        let stacked = stack_user_dictionaries(&[&lindera]).unwrap();
+        assert_eq!(
+            get_identifier(&stacked.dict.words_idx_data, &stacked.dict.words_data),
+            Some(&b""[..])
+        );
+        assert_eq!(export_prefix_dictionary(&stacked.dict).unwrap().len(), 2);
+        assert!(stack_user_dictionaries(&[&high, &lindera]).is_err());
+    }
+}
@@ -1,6 +1,27 @@
-use jpreprocess_core::word_line::WordDetailsLine;
+use jpreprocess_core::{error::DictionaryError, word_entry::WordEntry, word_line::WordDetailsLine};
 use lindera_dictionary::{error::LinderaErrorKind, LinderaResult};
 
+use crate::word_data::get_identifier;
+
+/// The literal of [`WORD_FORMAT_VERSION`], shared with the identifier.
+macro_rules! word_format_version {
+    () => {
+        1
+    };
+}
+/// The separator between the crate version and the word format version in the identifier.
+macro_rules! format_separator {
+    () => {
+        "; format "
+    };
+}
+
+/// The version of the layout of jpreprocess word data.
+///
+/// Bump this when the encoding of [`WordEntry`] changes, and keep decoding the older versions
+/// in [`JPreprocessDictionaryWordEncoding::deserialize_version`].
+pub const WORD_FORMAT_VERSION: u32 = word_format_version!();
+
 /// A trait for encoding and decoding as dictionary entry.
 pub trait DictionaryWordEncoding: Sized {
     fn identifier() -> &'static str;
@@ -9,18 +30,27 @@ pub trait DictionaryWordEncoding: Sized {
 
 pub struct JPreprocessDictionaryWordEncoding;
 impl JPreprocessDictionaryWordEncoding {
-    pub fn serialize(
-        data: &jpreprocess_core::word_entry::WordEntry,
-    ) -> Result<Vec<u8>, bincode::error::EncodeError> {
+    /// Encodes the word entry in the format of [`WORD_FORMAT_VERSION`].
+    pub fn serialize(data: &WordEntry) -> Result<Vec<u8>, bincode::error::EncodeError> {
         bincode::serde::encode_to_vec(data, Self::bincode_option())
     }
-    pub fn deserialize(
-        data: &[u8],
-    ) -> Result<jpreprocess_core::word_entry::WordEntry, bincode::error::DecodeError> {
+    /// Decodes the word entry in the format of [`WORD_FORMAT_VERSION`].
+    pub fn deserialize(data: &[u8]) -> Result<WordEntry, bincode::error::DecodeError> {
         let (decoded, _size) = bincode::serde::decode_from_slice(data, Self::bincode_option())?;
         Ok(decoded)
     }
 
+    /// Decodes the word entry in the format of the version.
+    pub fn deserialize_version(version: u32, data: &[u8]) -> Result<WordEntry, DictionaryError> {
+        match version {
+            1 => Ok(Self::deserialize(data)?),
+            found => Err(DictionaryError::UnsupportedFormatVersion {
+                found,
+                expected: WORD_FORMAT_VERSION,
+            }),
+        }
+    }
+
     fn bincode_option() -> bincode::config::Configuration {
         bincode::config::standard()
             .with_no_limit()
@@ -28,8 +58,14 @@ impl JPreprocessDictionaryWordEncoding {
     }
 }
 impl DictionaryWordEncoding for JPreprocessDictionaryWordEncoding {
+    /// `jpreprocess <crate version>; format <word format version>`.
     fn identifier() -> &'static str {
-        concat!("jpreprocess ", env!("CARGO_PKG_VERSION"))
+        concat!(
+            "jpreprocess ",
+            env!("CARGO_PKG_VERSION"),
+            format_separator!(),
+            word_format_version!()
+        )
     }
 
     fn encode(row: WordDetailsLine) -> LinderaResult<Vec<u8>> {
@@ -39,3 +75,128 @@ impl DictionaryWordEncoding for JPreprocessDictionaryWordEncoding {
         Self::serialize(&data).map_err(|err| LinderaErrorKind::Serialize.with_error(err))
     }
 }
+
+/// The word format of a jpreprocess dictionary, read once from its identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordFormat {
+    version: u32,
+}
+
+impl WordFormat {
+    /// Reads the word format version from the identifier of a jpreprocess dictionary.
+    ///
+    /// Dictionaries built before the version was recorded (`jpreprocess <crate version>`)
+    /// are of version 1 if they were built by JPreprocess 0.15, whose layout is version 1.
+    /// Those built by older releases are reported as
+    /// [`DictionaryError::UnsupportedLegacyVersion`], as their layout differs.
+    pub fn from_identifier(identifier: &str) -> Result<Self, DictionaryError> {
+        let invalid = || DictionaryError::InvalidFormatIdentifier(identifier.to_string());
+
+        let version = match identifier.split_once(format_separator!()) {
+            Some((_, version)) => version.trim().parse().map_err(|_| invalid())?,
+            None => {
+                let crate_version = identifier
+                    .split_once(' ')
+                    .map(|(_, crate_version)| crate_version.trim())
+                    .ok_or_else(invalid)?;
+                legacy_format_version(crate_version).ok_or_else(|| {
+                    DictionaryError::UnsupportedLegacyVersion(crate_version.to_string())
+                })?
+            }
+        };
+
+        if version > WORD_FORMAT_VERSION {
+            return Err(DictionaryError::UnsupportedFormatVersion {
+                found: version,
+                expected: WORD_FORMAT_VERSION,
+            });
+        }
+        Ok(Self { version })
+    }
+
+    /// Reads the word format of a jpreprocess dictionary from its word data.
+    pub fn from_idx_data(idx: &[u8], data: &[u8]) -> Result<Self, DictionaryError> {
+        let identifier = get_identifier(idx, data).unwrap_or_default();
+        match std::str::from_utf8(identifier) {
+            Ok(identifier) => Self::from_identifier(identifier),
+            Err(_) => Err(DictionaryError::InvalidFormatIdentifier(
+                String::from_utf8_lossy(identifier).into_owned(),
+            )),
+        }
+    }
+
+    pub fn version(self) -> u32 {
+        self.version
+    }
+
+    /// Decodes the word entry in this format.
+    pub fn deserialize(self, data: &[u8]) -> Result<WordEntry, DictionaryError> {
+        JPreprocessDictionaryWordEncoding::deserialize_version(self.version, data)
+    }
+}
+
+impl Default for WordFormat {
+    fn default() -> Self {
+        Self {
+            version: WORD_FORMAT_VERSION,
+        }
+    }
+}
+
+/// The word format of the dictionaries built by a release which did not record it.
+fn legacy_format_version(crate_version: &str) -> Option<u32> {
+    let mut parts = crate_version.split('.').map(str::parse::<u32>);
+    match (parts.next()?.ok()?, parts.next()?.ok()?) {
+        (0, 15) => Some(1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::word_data::get_word_data;
+
+    use super::*;
+
+    #[test]
+    fn format_version() {
+        let version = |identifier| WordFormat::from_identifier(identifier).map(WordFormat::version);
+        assert_eq!(
+            version(JPreprocessDictionaryWordEncoding::identifier()).unwrap(),
+            WORD_FORMAT_VERSION
+        );
+        assert_eq!(version("jpreprocess 0.15.0").unwrap(), 1);
+        assert!(matches!(
+            version("jpreprocess 0.12.0"),
+            Err(DictionaryError::UnsupportedLegacyVersion(found)) if found == "0.12.0"
+        ));
+        assert!(matches!(
+            version("jpreprocess 99.0.0; format 99"),
+            Err(DictionaryError::UnsupportedFormatVersion {
+                found: 99,
+                expected: WORD_FORMAT_VERSION
+            })
+        ));
+        assert!(matches!(
+            version("jpreprocess 99.0.0; format x"),
+            Err(DictionaryError::InvalidFormatIdentifier(identifier))
+                if identifier == "jpreprocess 99.0.0; format x"
+        ));
+        assert!(matches!(
+            version("jpreprocess"),
+            Err(DictionaryError::InvalidFormatIdentifier(_))
+        ));
+    }
+
+    #[test]
+    fn legacy_dictionary() {
+        let entry = WordEntry::default();
+        let mut data = b"jpreprocess 0.15.0".to_vec();
+        let idx = (data.len() as u32).to_le_bytes();
+        data.extend(JPreprocessDictionaryWordEncoding::serialize(&entry).unwrap());
+
+        let format = WordFormat::from_idx_data(&idx, &data).unwrap();
+        let word = get_word_data(&idx, &data, Some(0)).unwrap();
+        assert_eq!(format.deserialize(word).unwrap(), entry);
+    }
+}
@@ -2,4 +2,5 @@
 
 pub mod dictionary;
 pub mod tokenizer;
+mod util;
 pub mod word_data;
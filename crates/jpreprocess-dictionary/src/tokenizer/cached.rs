@@ -0,0 +1,411 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    num::NonZeroUsize,
+    ops::Range,
+    path::Path,
+    sync::Mutex,
+};
+
+use byteorder::{ByteOrder, LittleEndian};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
+use jpreprocess_core::{
+    error::DictionaryError,
+    token::{LinderaTokenSource, Token, Tokenizer},
+    word_entry::WordEntry,
+    JPreprocessResult,
+};
+use lindera_dictionary::{
+    dictionary::{Dictionary, UserDictionary},
+    error::LinderaErrorKind,
+    util::Data,
+    LinderaResult,
+};
+use lru::LruCache;
+
+use crate::{dictionary::word_encoding::WordFormat, word_data::get_word_data};
+
+use super::{
+    default::TokenizerType,
+    jpreprocess::{JPreprocessToken, JPreprocessTokenizer},
+};
+
+/// The number of words compressed together by [`CachedTokenizer::compressed`].
+const BLOCK_WORDS: usize = 256;
+/// The number of decompressed blocks kept by [`CachedTokenizer::compressed`].
+const DECODED_BLOCKS: usize = 8;
+
+/// Cache statistics of [`CachedTokenizer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of word entries in the cache.
+    pub len: usize,
+    pub capacity: usize,
+}
+
+/// A tokenizer which keeps the word details of the system dictionary out of memory,
+/// and decodes them on demand with a bounded cache of word entries.
+///
+/// Only the double array, the connection matrix and the unknown words are loaded,
+/// which reduces the memory usage of a jpreprocess dictionary considerably.
+/// User dictionaries are kept in memory as usual.
+pub struct CachedTokenizer {
+    tokenizer: lindera::tokenizer::Tokenizer,
+    user: Option<TokenizerType>,
+    words: Mutex<WordCache>,
+}
+
+impl CachedTokenizer {
+    /// Reads the word details from the files of a jpreprocess dictionary directory.
+    pub fn from_dir(path: &Path, capacity: NonZeroUsize) -> LinderaResult<Self> {
+        let open = |name: &str| {
+            File::open(path.join(name)).map_err(|err| {
+                LinderaErrorKind::Io
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context(format!("Failed to open {name} in {path:?}"))
+            })
+        };
+        let storage = WordStorage::File {
+            index: open("dict.wordsidx")?,
+            words: open("dict.words")?,
+        };
+
+        // The word data is memory-mapped, so it is not read here.
+        let dictionary = Dictionary::load_from_path_with_options(path, true)?;
+        Self::with_storage(dictionary, storage, capacity)
+    }
+
+    /// Compresses the word details of the dictionary, and keeps them in memory.
+    ///
+    /// The words are compressed in blocks of 256, and the last 8 decompressed blocks are kept,
+    /// so a cache miss decompresses a whole block unless the block is one of them.
+    /// This takes less memory than the word data itself, and no file access unlike
+    /// [`Self::from_dir`], at the cost of the decompression on cache misses.
+    /// Prefer [`Self::from_dir`] if the word data can be read from the page cache.
+    pub fn compressed(dictionary: Dictionary, capacity: NonZeroUsize) -> LinderaResult<Self> {
+        let storage = WordStorage::compress(
+            &dictionary.prefix_dictionary.words_idx_data,
+            &dictionary.prefix_dictionary.words_data,
+        )?;
+        Self::with_storage(dictionary, storage, capacity)
+    }
+
+    fn with_storage(
+        mut dictionary: Dictionary,
+        storage: WordStorage,
+        capacity: NonZeroUsize,
+    ) -> LinderaResult<Self> {
+        let TokenizerType::JPreprocessTokenizer(format) =
+            TokenizerType::identify(&dictionary.prefix_dictionary)
+                .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?
+        else {
+            return Err(LinderaErrorKind::Args.with_error(anyhow::anyhow!(
+                "Only jpreprocess dictionaries can be used with CachedTokenizer"
+            )));
+        };
+
+        dictionary.prefix_dictionary.words_idx_data = Data::Vec(vec![]);
+        dictionary.prefix_dictionary.words_data = Data::Vec(vec![]);
+
+        Ok(Self {
+            tokenizer: lindera::tokenizer::Tokenizer::new(lindera::segmenter::Segmenter::new(
+                lindera_dictionary::mode::Mode::Normal,
+                dictionary,
+                None,
+            )),
+            user: None,
+            words: Mutex::new(WordCache {
+                storage,
+                format,
+                cache: LruCache::new(capacity),
+                hits: 0,
+                misses: 0,
+            }),
+        })
+    }
+
+    /// Fails if the word format of a jpreprocess user dictionary cannot be read by this JPreprocess.
+    pub fn with_user_dictionary(
+        mut self,
+        user_dictionary: Option<UserDictionary>,
+    ) -> Result<Self, DictionaryError> {
+        self.user = user_dictionary
+            .as_ref()
+            .map(|d| TokenizerType::identify(&d.dict))
+            .transpose()?;
+        self.tokenizer.segmenter.user_dictionary = user_dictionary;
+        Ok(self)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let words = self.words.lock().unwrap_or_else(|err| err.into_inner());
+        CacheStats {
+            hits: words.hits,
+            misses: words.misses,
+            len: words.cache.len(),
+            capacity: words.cache.cap().get(),
+        }
+    }
+}
+
+impl Tokenizer for CachedTokenizer {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        let tokens = self.tokenizer.tokenize(text)?;
+        let mut words = self.words.lock().unwrap_or_else(|err| err.into_inner());
+        tokens
+            .into_iter()
+            .map(|mut token| {
+                let entry = if token.word_id.is_unknown() {
+                    token.fetch()?.1
+                } else if token.word_id.is_system() {
+                    words.get(token.word_id.id)?
+                } else {
+                    match self.user {
+                        Some(TokenizerType::JPreprocessTokenizer(format)) => {
+                            JPreprocessTokenizer::get_word_from_prefixdict(
+                                &token.user_dictionary.as_ref().unwrap().dict,
+                                token.word_id,
+                                format,
+                            )?
+                        }
+                        Some(TokenizerType::LinderaTokenizer) => token.fetch()?.1,
+                        None => Err(DictionaryError::UserDictionaryNotProvided)?,
+                    }
+                };
+                let source = LinderaTokenSource::new(&token);
+                Ok(JPreprocessToken::new(token.surface, entry).with_source(source))
+            })
+            .collect()
+    }
+}
+
+struct WordCache {
+    storage: WordStorage,
+    format: WordFormat,
+    cache: LruCache<u32, WordEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+impl WordCache {
+    fn get(&mut self, word_id: u32) -> JPreprocessResult<WordEntry> {
+        if let Some(entry) = self.cache.get(&word_id) {
+            self.hits += 1;
+            return Ok(entry.clone());
+        }
+        self.misses += 1;
+
+        let data = self
+            .storage
+            .read(word_id)?
+            .ok_or(DictionaryError::IdNotFound(word_id))?;
+        let entry = self.format.deserialize(&data)?;
+        self.cache.put(word_id, entry.clone());
+        Ok(entry)
+    }
+}
+
+enum WordStorage {
+    /// `dict.wordsidx` and `dict.words`.
+    File { index: File, words: File },
+    /// Blocks of [`BLOCK_WORDS`] words, each compressed with deflate,
+    /// and the recently decompressed ones.
+    ///
+    /// A decompressed block is a sequence of the word data prefixed with its length.
+    Compressed {
+        blocks: Vec<Vec<u8>>,
+        decoded: LruCache<usize, DecodedBlock>,
+    },
+}
+
+impl WordStorage {
+    fn compress(idx: &[u8], data: &[u8]) -> LinderaResult<Self> {
+        let words = (0..idx.len() / 4)
+            .map(|word_id| get_word_data(idx, data, Some(word_id)).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        let blocks = words
+            .chunks(BLOCK_WORDS)
+            .map(|block| {
+                let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                for word in block {
+                    encoder.write_all(&(word.len() as u32).to_le_bytes())?;
+                    encoder.write_all(word)?;
+                }
+                encoder.finish()
+            })
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(|err| {
+                LinderaErrorKind::Serialize
+                    .with_error(anyhow::anyhow!(err))
+                    .add_context("Failed to compress the word data")
+            })?;
+        Ok(Self::Compressed {
+            blocks,
+            decoded: LruCache::new(NonZeroUsize::new(DECODED_BLOCKS).unwrap()),
+        })
+    }
+
+    fn read(&mut self, word_id: u32) -> std::io::Result<Option<Vec<u8>>> {
+        match self {
+            Self::File { index, words } => {
+                let mut range = [0u8; 8];
+                index.seek(SeekFrom::Start(word_id as u64 * 4))?;
+                let len = read_up_to(index, &mut range)?;
+                if len < 4 {
+                    return Ok(None);
+                }
+                let start = LittleEndian::read_u32(&range[..4]);
+
+                words.seek(SeekFrom::Start(start as u64))?;
+                let mut data = Vec::new();
+                if len == 8 {
+                    let end = LittleEndian::read_u32(&range[4..]);
+                    words
+                        .take(end.saturating_sub(start) as u64)
+                        .read_to_end(&mut data)?;
+                } else {
+                    words.read_to_end(&mut data)?;
+                }
+                Ok(Some(data))
+            }
+            Self::Compressed { blocks, decoded } => {
+                let word_id = word_id as usize;
+                let Some(block) = blocks.get(word_id / BLOCK_WORDS) else {
+                    return Ok(None);
+                };
+                let block = decoded
+                    .try_get_or_insert(word_id / BLOCK_WORDS, || DecodedBlock::decode(block))?;
+                Ok(block.word(word_id % BLOCK_WORDS).map(<[u8]>::to_vec))
+            }
+        }
+    }
+}
+
+/// A decompressed block of [`WordStorage::Compressed`], with the range of each word.
+struct DecodedBlock {
+    data: Vec<u8>,
+    words: Vec<Range<usize>>,
+}
+
+impl DecodedBlock {
+    fn decode(block: &[u8]) -> std::io::Result<Self> {
+        let mut data = Vec::new();
+        DeflateDecoder::new(block).read_to_end(&mut data)?;
+
+        let mut words = Vec::with_capacity(BLOCK_WORDS);
+        let mut offset = 0;
+        while let Some(len) = data.get(offset..offset + 4) {
+            let start = offset + 4;
+            offset = start + LittleEndian::read_u32(len) as usize;
+            words.push(start..offset);
+        }
+        Ok(Self { data, words })
+    }
+
+    fn word(&self, index: usize) -> Option<&[u8]> {
+        self.data.get(self.words.get(index)?.clone())
+    }
+}
+
+/// Reads until the buffer is filled or the end of the file, and returns the bytes read.
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        dictionary::convert::{convert_dictionary, WordEncoding},
+        tokenizer::{default::DefaultTokenizer, estimate::DictionarySchema},
+        util::TempDir,
+    };
+
+    fn entries(tokenizer: &impl Tokenizer, text: &str) -> Vec<(String, WordEntry)> {
+        tokenizer
+            .tokenize(text)
+            .unwrap()
+            .into_iter()
+            .map(|mut token| {
+                let (surface, entry) = token.fetch().unwrap();
+                (surface.to_string(), entry)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compressed_storage() {
+        let words = (0..BLOCK_WORDS * 2 + 10)
+            .map(|i| vec![i as u8; i % 7])
+            .collect::<Vec<_>>();
+        let mut idx = Vec::new();
+        let mut data = Vec::new();
+        for word in &words {
+            idx.extend((data.len() as u32).to_le_bytes());
+            data.extend(word);
+        }
+
+        let mut storage = WordStorage::compress(&idx, &data).unwrap();
+        for word_id in [0, 1, BLOCK_WORDS - 1, BLOCK_WORDS, BLOCK_WORDS * 2 + 9, 3] {
+            assert_eq!(
+                storage.read(word_id as u32).unwrap().as_ref(),
+                Some(&words[word_id])
+            );
+        }
+        assert_eq!(storage.read((BLOCK_WORDS * 2 + 10) as u32).unwrap(), None);
+        assert_eq!(storage.read((BLOCK_WORDS * 3) as u32).unwrap(), None);
+    }
+
+    #[test]
+    fn cached() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        convert_dictionary(
+            &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/data/min-dict"),
+            path,
+            WordEncoding::JPreprocess,
+            DictionarySchema::NaistJdic,
+        )
+        .unwrap();
+        let text = "音声合成エンジンに渡せる形式に変換します。音声を合成します";
+
+        let dictionary = lindera::dictionary::load_fs_dictionary(path).unwrap();
+        let expected = entries(
+            &DefaultTokenizer::new(lindera::tokenizer::Tokenizer::new(
+                lindera::segmenter::Segmenter::new(
+                    lindera_dictionary::mode::Mode::Normal,
+                    dictionary.clone(),
+                    None,
+                ),
+            ))
+            .unwrap(),
+            text,
+        );
+
+        let capacity = NonZeroUsize::new(4).unwrap();
+        for tokenizer in [
+            CachedTokenizer::from_dir(path, capacity).unwrap(),
+            CachedTokenizer::compressed(dictionary.clone(), capacity).unwrap(),
+        ] {
+            assert_eq!(entries(&tokenizer, text), expected);
+
+            let stats = tokenizer.stats();
+            assert!(stats.hits > 0);
+            assert!(stats.misses > 0);
+            assert_eq!(stats.len, 4);
+            assert_eq!(stats.capacity, 4);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use jpreprocess_core::{
-    token::{Token, Tokenizer},
+    error::DictionaryError,
+    token::{LinderaTokenSource, Token, TokenMetadata, Tokenizer},
     word_entry::WordEntry,
     JPreprocessResult,
 };
-use lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary;
+use lindera_dictionary::dictionary::{prefix_dictionary::PrefixDictionary, UserDictionary};
+
+use crate::dictionary::word_encoding::WordFormat;
 
 use super::{
+    estimate::DictionarySchema,
     identify_dictionary::DictionaryIdent,
     jpreprocess::{JPreprocessToken, JPreprocessTokenizer},
 };
@@ -14,90 +18,162 @@ pub struct DefaultTokenizer {
     lindera_tokenizer: lindera::tokenizer::Tokenizer,
     system: TokenizerType,
     user: Option<TokenizerType>,
+    schema: DictionarySchema,
 }
 
-enum TokenizerType {
-    JPreprocessTokenizer,
+#[derive(Clone, Copy)]
+pub(super) enum TokenizerType {
+    JPreprocessTokenizer(WordFormat),
     LinderaTokenizer,
 }
 
-impl DefaultTokenizer {
-    pub fn new(tokenizer: lindera::tokenizer::Tokenizer) -> Self {
-        fn identify_tokenizer(prefix_dictionary: &PrefixDictionary) -> TokenizerType {
-            let ident = DictionaryIdent::from_idx_data(
-                &prefix_dictionary.words_idx_data,
-                &prefix_dictionary.words_data,
-            );
-            match ident {
-                DictionaryIdent::JPreprocess => TokenizerType::JPreprocessTokenizer,
-                DictionaryIdent::Lindera => TokenizerType::LinderaTokenizer,
-            }
-        }
+impl TokenizerType {
+    /// Identifies the dictionary, and checks that its word format can be read.
+    pub(super) fn identify(prefix_dictionary: &PrefixDictionary) -> Result<Self, DictionaryError> {
+        let ident = DictionaryIdent::from_idx_data(
+            &prefix_dictionary.words_idx_data,
+            &prefix_dictionary.words_data,
+        )?;
+        Ok(match ident {
+            DictionaryIdent::JPreprocess(format) => Self::JPreprocessTokenizer(format),
+            DictionaryIdent::Lindera => Self::LinderaTokenizer,
+        })
+    }
+}
 
-        Self {
-            system: identify_tokenizer(&tokenizer.segmenter.dictionary.prefix_dictionary),
+impl DefaultTokenizer {
+    /// Fails if the word format of a jpreprocess dictionary cannot be read by this JPreprocess.
+    pub fn new(tokenizer: lindera::tokenizer::Tokenizer) -> Result<Self, DictionaryError> {
+        Ok(Self {
+            system: TokenizerType::identify(&tokenizer.segmenter.dictionary.prefix_dictionary)?,
             user: tokenizer
                 .segmenter
                 .user_dictionary
                 .as_ref()
-                .map(|d| identify_tokenizer(&d.dict)),
+                .map(|d| TokenizerType::identify(&d.dict))
+                .transpose()?,
             lindera_tokenizer: tokenizer,
-        }
+            schema: DictionarySchema::default(),
+        })
+    }
+
+    /// Sets the schema of lindera dictionaries.
+    ///
+    /// This does not affect jpreprocess dictionaries, which always have the naist-jdic schema.
+    pub fn with_schema(self, schema: DictionarySchema) -> Self {
+        Self { schema, ..self }
+    }
+
+    /// Replaces the user dictionary.
+    pub fn set_user_dictionary(
+        &mut self,
+        user_dictionary: Option<UserDictionary>,
+    ) -> Result<(), DictionaryError> {
+        self.user = user_dictionary
+            .as_ref()
+            .map(|d| TokenizerType::identify(&d.dict))
+            .transpose()?;
+        self.lindera_tokenizer.segmenter.user_dictionary = user_dictionary;
+        Ok(())
     }
 }
 
 impl Tokenizer for DefaultTokenizer {
     fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
         let tokens = self.lindera_tokenizer.tokenize(text)?;
+        decode_tokens(tokens, &self.system, self.user.as_ref(), self.schema)
+    }
+}
 
-        tokens
-            .into_iter()
-            .map(|token| {
-                if token.word_id.is_unknown() {
-                    Ok(DefaultToken::from_token(token))
-                } else if token.word_id.is_system() {
-                    match self.system {
-                        TokenizerType::JPreprocessTokenizer => {
-                            Ok(DefaultToken::from_token(JPreprocessToken::new(
-                                token.surface,
-                                JPreprocessTokenizer::get_word_from_prefixdict(
-                                    &token.dictionary.prefix_dictionary,
-                                    token.word_id,
-                                )?,
-                            )))
-                        }
-                        TokenizerType::LinderaTokenizer => Ok(DefaultToken::from_token(token)),
-                    }
-                } else {
-                    match self.user {
-                        Some(TokenizerType::JPreprocessTokenizer) => {
-                            Ok(DefaultToken::from_token(JPreprocessToken::new(
-                                token.surface,
-                                JPreprocessTokenizer::get_word_from_prefixdict(
-                                    &token.user_dictionary.as_ref().unwrap().dict,
-                                    token.word_id,
-                                )?,
-                            )))
-                        }
-                        Some(TokenizerType::LinderaTokenizer) => {
-                            Ok(DefaultToken::from_token(token))
-                        }
-                        None => Ok(DefaultToken::from_token(token)),
-                    }
-                }
-            })
-            .collect()
+/// Reads the word entries of the tokens from the dictionaries of the given types.
+pub(super) fn decode_tokens<'a>(
+    tokens: Vec<lindera::token::Token<'a>>,
+    system: &TokenizerType,
+    user: Option<&TokenizerType>,
+    schema: DictionarySchema,
+) -> JPreprocessResult<Vec<DefaultToken<'a>>> {
+    tokens
+        .into_iter()
+        .map(|token| {
+            let source = LinderaTokenSource::new(&token);
+            decode_token(token, system, user, schema).map(|token| token.with_source(source))
+        })
+        .collect()
+}
+
+/// Reads the word entry of a token from the dictionaries of the given types.
+pub(super) fn decode_token<'a>(
+    token: lindera::token::Token<'a>,
+    system: &TokenizerType,
+    user: Option<&TokenizerType>,
+    schema: DictionarySchema,
+) -> JPreprocessResult<DefaultToken<'a>> {
+    if token.word_id.is_unknown() {
+        Ok(DefaultToken::from_token(token))
+    } else if token.word_id.is_system() {
+        match system {
+            TokenizerType::JPreprocessTokenizer(format) => {
+                Ok(DefaultToken::from_token(JPreprocessToken::new(
+                    token.surface,
+                    JPreprocessTokenizer::get_word_from_prefixdict(
+                        &token.dictionary.prefix_dictionary,
+                        token.word_id,
+                        *format,
+                    )?,
+                )))
+            }
+            TokenizerType::LinderaTokenizer => lindera_token(token, schema),
+        }
+    } else {
+        match user {
+            Some(TokenizerType::JPreprocessTokenizer(format)) => {
+                Ok(DefaultToken::from_token(JPreprocessToken::new(
+                    token.surface,
+                    JPreprocessTokenizer::get_word_from_prefixdict(
+                        &token.user_dictionary.as_ref().unwrap().dict,
+                        token.word_id,
+                        *format,
+                    )?,
+                )))
+            }
+            Some(TokenizerType::LinderaTokenizer) => lindera_token(token, schema),
+            None => Ok(DefaultToken::from_token(token)),
+        }
     }
 }
 
-struct DefaultToken<'a> {
+fn lindera_token(
+    mut token: lindera::token::Token<'_>,
+    schema: DictionarySchema,
+) -> JPreprocessResult<DefaultToken<'_>> {
+    match schema {
+        DictionarySchema::NaistJdic => Ok(DefaultToken::from_token(token)),
+        schema => {
+            let entry = schema.load_word_entry(&token.details())?;
+            Ok(DefaultToken::from_token((
+                token.surface.into_owned(),
+                entry,
+            )))
+        }
+    }
+}
+
+pub(super) struct DefaultToken<'a> {
     inner: Box<dyn 'a + Token>,
+    source: Option<LinderaTokenSource<'a>>,
 }
 
 impl<'a> DefaultToken<'a> {
     fn from_token(inner: impl 'a + Token) -> Self {
         DefaultToken {
             inner: Box::new(inner),
+            source: None,
+        }
+    }
+    fn with_source(self, source: LinderaTokenSource<'a>) -> Self {
+        Self {
+            source: Some(source),
+            ..self
         }
     }
 }
@@ -106,4 +182,10 @@ impl Token for DefaultToken<'_> {
     fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
         self.inner.fetch()
     }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        match &self.source {
+            Some(source) => Some(source.metadata()),
+            None => self.inner.metadata(),
+        }
+    }
 }
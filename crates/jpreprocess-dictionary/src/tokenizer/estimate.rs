@@ -0,0 +1,181 @@
+//! Estimation of accent fields for dictionaries without them.
+
+use std::str::FromStr;
+
+use jpreprocess_core::{
+    accent_rule::ChainRules,
+    cform::CForm,
+    ctype::CType,
+    pos::{Meishi, POS},
+    pronunciation::Pronunciation,
+    word_details::WordDetails,
+    word_entry::WordEntry,
+    JPreprocessResult,
+};
+
+use super::unidic::{map_unidic_pos, MappedPos};
+
+/// Layout of the word details in a lindera dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DictionarySchema {
+    /// naist-jdic with the jpreprocess accent fields (accent/mora size, chain rule, chain flag).
+    #[default]
+    NaistJdic,
+    /// Plain IPADIC (e.g. lindera-ipadic).
+    ///
+    /// Accent type, mora size and chain rule are estimated from POS and pronunciation.
+    Ipadic,
+    /// Plain UniDic (e.g. lindera-unidic).
+    ///
+    /// POS is mapped onto the naist-jdic schema, and accent type, mora size and chain rule
+    /// are estimated from POS and pronunciation.
+    Unidic,
+}
+
+impl DictionarySchema {
+    /// Loads the word details of a lindera dictionary entry according to the schema.
+    pub fn load_word_entry(&self, details: &[&str]) -> JPreprocessResult<WordEntry> {
+        match self {
+            Self::NaistJdic => {
+                let mut details = details.to_vec();
+                details.resize(12, "");
+                WordEntry::load(&details)
+            }
+            Self::Ipadic => {
+                let field = |i: usize| details.get(i).copied().unwrap_or("*");
+                let mapped = MappedPos {
+                    pos: POS::from_strs(field(0), field(1), field(2), field(3))?,
+                    ctype: CType::from_str(field(4))?,
+                    cform: CForm::from_str(field(5))?,
+                };
+                Ok(estimate_word_entry(mapped, field(7), field(8)))
+            }
+            Self::Unidic => {
+                // 品詞1-4, 活用型, 活用形, 語彙素読み, 語彙素, 書字形出現形, 発音形出現形, ...
+                let pron = details.get(9).copied().unwrap_or("*");
+                Ok(estimate_word_entry(map_unidic_pos(details), pron, pron))
+            }
+        }
+    }
+}
+
+/// Creates a word entry, estimating the accent and the chain rule.
+pub fn estimate_word_entry(mapped: MappedPos, read: &str, pron: &str) -> WordEntry {
+    let mut pron = Pronunciation::parse(pron, 0).unwrap_or_default();
+    pron.set_accent(estimate_accent(&mapped.pos, pron.mora_size()));
+
+    WordEntry::Single(WordDetails {
+        pos: mapped.pos,
+        ctype: mapped.ctype,
+        cform: mapped.cform,
+        read: match read {
+            "*" | "" => None,
+            read => Some(read.to_string()),
+        },
+        pron,
+        chain_rule: estimate_chain_rule(&mapped.pos),
+        chain_flag: None,
+    })
+}
+
+/// Estimates the accent nucleus from the most common pattern of the POS.
+///
+/// - Short nouns (up to two moras) are accented on the first mora, and longer ones are flat.
+/// - Adjectives are accented on the penultimate mora (e.g. タカ\イ).
+/// - Adverbs, conjunctions, interjections and adnominals are accented on the first mora.
+/// - Others, including verbs, particles and auxiliary verbs, are flat.
+pub fn estimate_accent(pos: &POS, mora_size: usize) -> usize {
+    match pos {
+        POS::Meishi(Meishi::SahenSetsuzoku | Meishi::Kazu) => 0,
+        POS::Meishi(_) if mora_size <= 2 => mora_size.min(1),
+        POS::Keiyoushi(_) => mora_size.saturating_sub(1),
+        POS::Fukushi(_) | POS::Setsuzokushi | POS::Kandoushi | POS::Rentaishi => mora_size.min(1),
+        _ => 0,
+    }
+}
+
+/// Estimates the chain rule from the POS.
+pub fn estimate_chain_rule(pos: &POS) -> ChainRules {
+    ChainRules::new(match pos {
+        POS::Meishi(Meishi::SahenSetsuzoku) => "C2",
+        POS::Meishi(_) => "C1",
+        POS::Joshi(_) => "動詞%F5/形容詞%F1/名詞%F1",
+        _ => "*",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::pos::{Doushi, KoyuMeishi, Region};
+
+    use super::*;
+
+    #[test]
+    fn ipadic() {
+        let entry = DictionarySchema::Ipadic
+            .load_word_entry(&[
+                "名詞",
+                "固有名詞",
+                "地域",
+                "国",
+                "*",
+                "*",
+                "日本",
+                "ニッポン",
+                "ニッポン",
+            ])
+            .unwrap();
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(
+            details.pos,
+            POS::Meishi(Meishi::KoyuMeishi(KoyuMeishi::Region(Region::Country)))
+        );
+        assert_eq!(details.pron.mora_size(), 4);
+        assert_eq!(details.pron.accent(), 0);
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+    }
+
+    #[test]
+    fn unidic() {
+        let entry = DictionarySchema::Unidic
+            .load_word_entry(&[
+                "動詞",
+                "一般",
+                "*",
+                "*",
+                "五段-カ行",
+                "終止形-一般",
+                "カク",
+                "書く",
+                "書く",
+                "カク",
+                "書く",
+                "カク",
+                "和",
+                "*",
+                "*",
+                "*",
+                "*",
+            ])
+            .unwrap();
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pos, POS::Doushi(Doushi::Jiritsu));
+        assert_eq!(details.cform, CForm::Basic);
+        assert_eq!(details.pron.to_string(), "カク");
+        assert_eq!(details.read.as_deref(), Some("カク"));
+    }
+
+    #[test]
+    fn accent() {
+        let keiyoushi = POS::from_strs("形容詞", "自立", "*", "*").unwrap();
+        assert_eq!(estimate_accent(&keiyoushi, 3), 2);
+
+        let meishi = POS::Meishi(Meishi::General);
+        assert_eq!(estimate_accent(&meishi, 2), 1);
+        assert_eq!(estimate_accent(&meishi, 4), 0);
+    }
+}
@@ -1,26 +1,29 @@
-use crate::word_data::get_word_data;
+use jpreprocess_core::error::DictionaryError;
+
+use crate::{dictionary::word_encoding::WordFormat, word_data::get_identifier};
 
 pub enum DictionaryIdent {
     Lindera,
-    JPreprocess,
+    JPreprocess(WordFormat),
 }
 
 impl DictionaryIdent {
-    pub fn from_idx_data(idx: &[u8], data: &[u8]) -> Self {
-        let Some(data) = get_word_data(idx, data, None) else {
-            return DictionaryIdent::Lindera;
+    /// Identifies the dictionary, and checks that its word format can be read.
+    pub fn from_idx_data(idx: &[u8], data: &[u8]) -> Result<Self, DictionaryError> {
+        let Some(data) = get_identifier(idx, data) else {
+            return Ok(DictionaryIdent::Lindera);
         };
 
         match std::str::from_utf8(data) {
-            Ok("") => DictionaryIdent::Lindera,
-            Ok(ident) if ident.to_lowercase().starts_with("jpreprocess") => {
-                DictionaryIdent::JPreprocess
-            }
+            Ok("") => Ok(DictionaryIdent::Lindera),
+            Ok(ident) if ident.to_lowercase().starts_with("jpreprocess") => Ok(
+                DictionaryIdent::JPreprocess(WordFormat::from_identifier(ident)?),
+            ),
             Err(e) => {
                 eprintln!("Error parsing dictionary type: {}", e);
-                DictionaryIdent::Lindera
+                Ok(DictionaryIdent::Lindera)
             }
-            _ => DictionaryIdent::Lindera,
+            _ => Ok(DictionaryIdent::Lindera),
         }
     }
 }
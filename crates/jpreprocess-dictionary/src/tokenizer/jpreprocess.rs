@@ -1,23 +1,38 @@
 use std::borrow::Cow;
 
+use lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary;
+
 use jpreprocess_core::{
     error::DictionaryError,
-    token::{Token, Tokenizer},
+    token::{LinderaTokenSource, Token, TokenMetadata, Tokenizer},
     word_entry::WordEntry,
     JPreprocessResult,
 };
 
-use crate::{
-    dictionary::word_encoding::JPreprocessDictionaryWordEncoding, word_data::get_word_data,
-};
+use crate::{dictionary::word_encoding::WordFormat, word_data::get_word_data};
 
 pub struct JPreprocessTokenizer {
     tokenizer: lindera::tokenizer::Tokenizer,
+    system: WordFormat,
+    user: Option<WordFormat>,
 }
 
 impl JPreprocessTokenizer {
-    pub fn new(tokenizer: lindera::tokenizer::Tokenizer) -> Self {
-        Self { tokenizer }
+    /// Fails if the word format of a dictionary cannot be read by this JPreprocess.
+    pub fn new(tokenizer: lindera::tokenizer::Tokenizer) -> Result<Self, DictionaryError> {
+        let format = |dict: &PrefixDictionary| {
+            WordFormat::from_idx_data(&dict.words_idx_data, &dict.words_data)
+        };
+        Ok(Self {
+            system: format(&tokenizer.segmenter.dictionary.prefix_dictionary)?,
+            user: tokenizer
+                .segmenter
+                .user_dictionary
+                .as_ref()
+                .map(|user| format(&user.dict))
+                .transpose()?,
+            tokenizer,
+        })
     }
 
     fn get_word(
@@ -30,20 +45,26 @@ impl JPreprocessTokenizer {
             Self::get_word_from_prefixdict(
                 &self.tokenizer.segmenter.dictionary.prefix_dictionary,
                 word_id,
+                self.system,
             )
         } else {
             let user = &self.tokenizer.segmenter.user_dictionary;
-            user.as_ref()
-                .map_or(Err(DictionaryError::UserDictionaryNotProvided), |user| {
-                    Self::get_word_from_prefixdict(&user.dict, word_id)
-                })
+            match (user, self.user) {
+                (Some(user), Some(format)) => {
+                    Self::get_word_from_prefixdict(&user.dict, word_id, format)
+                }
+                _ => Err(DictionaryError::UserDictionaryNotProvided),
+            }
         }
     }
 
     /// PANIC: It must be ensured that the prefix_dict is the correct dictionary for the word_id.
+    ///
+    /// `format` is the word format of `prefix_dict`, read once when the tokenizer is created.
     pub(super) fn get_word_from_prefixdict(
-        prefix_dict: &lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary,
+        prefix_dict: &PrefixDictionary,
         word_id: lindera_dictionary::viterbi::WordId,
+        format: WordFormat,
     ) -> Result<WordEntry, DictionaryError> {
         if word_id.is_unknown() {
             Ok(WordEntry::default())
@@ -54,7 +75,7 @@ impl JPreprocessTokenizer {
                 Some(word_id.id as usize),
             )
             .ok_or(DictionaryError::IdNotFound(word_id.id))?;
-            Ok(JPreprocessDictionaryWordEncoding::deserialize(data)?)
+            format.deserialize(data)
         }
     }
 }
@@ -65,10 +86,9 @@ impl Tokenizer for JPreprocessTokenizer {
         words
             .into_iter()
             .map(|token| {
-                Ok(JPreprocessToken::new(
-                    token.surface,
-                    self.get_word(token.word_id)?,
-                ))
+                let word = self.get_word(token.word_id)?;
+                let source = LinderaTokenSource::new(&token);
+                Ok(JPreprocessToken::new(token.surface, word).with_source(source))
             })
             .collect::<Result<_, _>>()
     }
@@ -77,11 +97,22 @@ impl Tokenizer for JPreprocessTokenizer {
 pub struct JPreprocessToken<'a> {
     text: Cow<'a, str>,
     entry: WordEntry,
+    source: Option<LinderaTokenSource<'a>>,
 }
 
 impl<'a> JPreprocessToken<'a> {
     pub(crate) fn new(text: Cow<'a, str>, entry: WordEntry) -> Self {
-        Self { text, entry }
+        Self {
+            text,
+            entry,
+            source: None,
+        }
+    }
+    pub(crate) fn with_source(self, source: LinderaTokenSource<'a>) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
     }
 }
 
@@ -89,4 +120,7 @@ impl Token for JPreprocessToken<'_> {
     fn fetch(&mut self) -> Result<(&str, WordEntry), jpreprocess_core::JPreprocessError> {
         Ok((&self.text, self.entry.clone()))
     }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        self.source.as_ref().map(LinderaTokenSource::metadata)
+    }
 }
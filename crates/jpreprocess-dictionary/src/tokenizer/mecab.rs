@@ -0,0 +1,125 @@
+use jpreprocess_core::{
+    token::{Token, Tokenizer},
+    word_entry::WordEntry,
+    JPreprocessResult,
+};
+
+/// A tokenizer that reads the default output format of MeCab instead of analyzing the text.
+///
+/// Each line is `surface\tfeature1,feature2,...`, and `EOS` lines and empty lines are skipped.
+/// The features are read in the same way as lindera dictionaries, so the output of
+/// MeCab with naist-jdic (OpenJTalk) is loaded with accents.
+///
+/// As the input is not a plain text, pass the tokens directly to `NJD::from_tokens`
+/// instead of using `JPreprocess::text_to_njd`, which normalizes the input.
+///
+/// ```rust
+/// # use jpreprocess_core::token::*;
+/// use jpreprocess_dictionary::tokenizer::mecab::MecabOutputTokenizer;
+///
+/// let output = "日本\t名詞,固有名詞,地域,国,*,*,日本,ニッポン,ニッポン,3/4,C1,-1\nEOS\n";
+/// let mut tokens = MecabOutputTokenizer.tokenize(output).unwrap();
+/// let (surface, _entry) = tokens[0].fetch().unwrap();
+/// assert_eq!(surface, "日本");
+/// ```
+pub struct MecabOutputTokenizer;
+
+impl Tokenizer for MecabOutputTokenizer {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && *line != "EOS")
+            .map(|(i, line)| {
+                let (surface, features) = line.split_once('\t').ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("line {}: expected `surface\\tfeatures`: {}", i + 1, line),
+                    )
+                })?;
+                Ok(MecabToken { surface, features })
+            })
+            .collect()
+    }
+}
+
+struct MecabToken<'a> {
+    surface: &'a str,
+    features: &'a str,
+}
+
+impl Token for MecabToken<'_> {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        let mut details = self.features.split(',').collect::<Vec<_>>();
+        details.resize(12, "");
+        Ok((self.surface, WordEntry::load(&details)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::pos::{Kigou, POS};
+
+    use super::*;
+
+    #[test]
+    fn mecab_output() {
+        let output = concat!(
+            "日本\t名詞,固有名詞,地域,国,*,*,日本,ニッポン,ニッポン,3/4,C1,-1\n",
+            "語\t名詞,接尾,一般,*,*,*,語,ゴ,ゴ,1/1,C1,-1\n",
+            "。\t記号,句点,*,*,*,*,。,、,、,0/0,*,-1\n",
+            "EOS\n",
+            "\n",
+            "テスト\t名詞,サ変接続,*,*,*,*,テスト,テスト,テスト,1/3,C1,-1\n",
+            "EOS\n",
+        );
+
+        let mut tokens = MecabOutputTokenizer.tokenize(output).unwrap();
+        assert_eq!(tokens.len(), 4);
+
+        let (surface, entry) = tokens[0].fetch().unwrap();
+        assert_eq!(surface, "日本");
+        assert_eq!(
+            entry,
+            WordEntry::load(&[
+                "名詞",
+                "固有名詞",
+                "地域",
+                "国",
+                "*",
+                "*",
+                "日本",
+                "ニッポン",
+                "ニッポン",
+                "3/4",
+                "C1",
+                "-1"
+            ])
+            .unwrap()
+        );
+
+        let (surface, entry) = tokens[2].fetch().unwrap();
+        assert_eq!(surface, "。");
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pos, POS::Kigou(Kigou::Kuten));
+    }
+
+    #[test]
+    fn ipadic_output() {
+        // Plain IPADIC output lacks the accent fields.
+        let mut tokens = MecabOutputTokenizer
+            .tokenize("日本\t名詞,固有名詞,地域,国,*,*,日本,ニッポン,ニッポン\nEOS\n")
+            .unwrap();
+        let (_, entry) = tokens[0].fetch().unwrap();
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pron.to_string(), "ニッポン");
+    }
+
+    #[test]
+    fn invalid_line() {
+        assert!(MecabOutputTokenizer.tokenize("日本 名詞\nEOS\n").is_err());
+    }
+}
@@ -1,3 +1,11 @@
+pub mod cached;
 pub mod default;
-mod identify_dictionary;
+pub mod estimate;
+pub(crate) mod identify_dictionary;
 pub mod jpreprocess;
+pub mod mecab;
+pub mod mutable;
+pub mod shared;
+#[cfg(feature = "sudachi-adapter")]
+pub mod sudachi_adapter;
+pub mod unidic;
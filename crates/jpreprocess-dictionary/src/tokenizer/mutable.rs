@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use jpreprocess_core::{
+    token::{LinderaTokenSource, Token, TokenMetadata, Tokenizer},
+    word_entry::WordEntry,
+    JPreprocessResult,
+};
+use lindera_dictionary::{
+    dictionary::UserDictionary, error::LinderaErrorKind, viterbi::WordId, LinderaResult,
+};
+
+use crate::dictionary::{export::ExportedWord, user::UserDictionaryEditor};
+
+use super::shared::SharedDictionaryTokenizer;
+
+/// A [`SharedDictionaryTokenizer`] whose user dictionary can be edited while it is in use.
+///
+/// An edit builds a new user dictionary, which then replaces the current one at once,
+/// so each `tokenize` call sees either the old or the new dictionary.
+pub struct MutableUserDictionaryTokenizer {
+    current: RwLock<Arc<Snapshot>>,
+    edit: Mutex<()>,
+}
+
+/// The user dictionary and the words it was built from.
+struct Snapshot {
+    tokenizer: SharedDictionaryTokenizer,
+    editor: UserDictionaryEditor,
+}
+
+impl MutableUserDictionaryTokenizer {
+    /// Creates the tokenizer with the words of `editor` as the user dictionary.
+    ///
+    /// The user dictionary of `tokenizer` is replaced.
+    pub fn new(
+        tokenizer: SharedDictionaryTokenizer,
+        editor: UserDictionaryEditor,
+    ) -> LinderaResult<Self> {
+        Ok(Self {
+            current: RwLock::new(Arc::new(Snapshot::new(&tokenizer, editor)?)),
+            edit: Mutex::new(()),
+        })
+    }
+
+    /// Edits the user dictionary.
+    ///
+    /// The edit is discarded if `f` or building the dictionary fails.
+    ///
+    /// Each edit copies the words and builds the whole user dictionary again, which takes time
+    /// in proportion to the number of words, so make the changes in as few edits as possible.
+    /// Edits are applied one at a time, but `tokenize` is not blocked while the dictionary is built;
+    /// it keeps using the old dictionary until the new one replaces it.
+    pub fn edit<R>(
+        &self,
+        f: impl FnOnce(&mut UserDictionaryEditor) -> LinderaResult<R>,
+    ) -> LinderaResult<R> {
+        let _edit = self.edit.lock().unwrap_or_else(|err| err.into_inner());
+        let current = self.snapshot();
+
+        let mut editor = current.editor.clone();
+        let result = f(&mut editor)?;
+        let snapshot = Snapshot::new(&current.tokenizer, editor)?;
+
+        *self.current.write().unwrap_or_else(|err| err.into_inner()) = Arc::new(snapshot);
+
+        Ok(result)
+    }
+
+    /// The words of the current user dictionary.
+    pub fn words(&self) -> Vec<ExportedWord> {
+        self.snapshot().editor.words().to_vec()
+    }
+
+    fn snapshot(&self) -> Arc<Snapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl Snapshot {
+    fn new(
+        tokenizer: &SharedDictionaryTokenizer,
+        editor: UserDictionaryEditor,
+    ) -> LinderaResult<Self> {
+        Ok(Self {
+            tokenizer: tokenizer
+                .with_user_dictionary(build(&editor)?.map(Arc::new))
+                .map_err(|err| LinderaErrorKind::Deserialize.with_error(err))?,
+            editor,
+        })
+    }
+}
+
+/// An empty user dictionary cannot be built, so it is replaced with `None`.
+fn build(editor: &UserDictionaryEditor) -> LinderaResult<Option<UserDictionary>> {
+    if editor.words().is_empty() {
+        Ok(None)
+    } else {
+        editor.build().map(Some)
+    }
+}
+
+impl Tokenizer for MutableUserDictionaryTokenizer {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        let snapshot = self.snapshot();
+
+        // The tokens keep the dictionary they were segmented with, even after an edit.
+        let tokens = snapshot.tokenizer.segment(text);
+        let tokens = tokens
+            .into_iter()
+            .map(|token| MutableToken {
+                snapshot: snapshot.clone(),
+                surface: &text[token.byte_start..token.byte_end],
+                byte_start: token.byte_start,
+                byte_end: token.byte_end,
+                position: token.position,
+                word_id: token.word_id,
+            })
+            .collect();
+        Ok(tokens)
+    }
+}
+
+struct MutableToken<'a> {
+    snapshot: Arc<Snapshot>,
+    surface: &'a str,
+    byte_start: usize,
+    byte_end: usize,
+    position: usize,
+    word_id: WordId,
+}
+
+impl MutableToken<'_> {
+    fn token(&self) -> lindera::token::Token<'_> {
+        self.snapshot.tokenizer.token(
+            self.surface,
+            self.byte_start,
+            self.byte_end,
+            self.position,
+            self.word_id,
+        )
+    }
+}
+
+impl Token for MutableToken<'_> {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        let mut token = self.snapshot.tokenizer.decode(self.token())?;
+        let (_, entry) = token.fetch()?;
+        Ok((self.surface, entry))
+    }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        Some(LinderaTokenSource::new(&self.token()).metadata())
+    }
+}
@@ -0,0 +1,214 @@
+use std::{borrow::Cow, sync::Arc};
+
+use jpreprocess_core::{
+    error::DictionaryError,
+    token::{Token, Tokenizer},
+    JPreprocessResult,
+};
+use lindera_dictionary::{
+    dictionary::{character_definition::CategoryId, Dictionary, UserDictionary},
+    mode::Mode,
+    viterbi::{Lattice, WordId},
+};
+
+use super::{
+    default::{decode_token, decode_tokens, DefaultToken, TokenizerType},
+    estimate::DictionarySchema,
+};
+
+/// A tokenizer sharing the system dictionary with other tokenizers.
+///
+/// The system dictionary is never copied, so a tokenizer with another user dictionary
+/// can be created for each request with [`SharedDictionaryTokenizer::with_user_dictionary`].
+/// Tokens are the same as [`DefaultTokenizer`](super::default::DefaultTokenizer).
+#[derive(Clone)]
+pub struct SharedDictionaryTokenizer {
+    dictionary: Arc<Dictionary>,
+    user_dictionary: Option<Arc<UserDictionary>>,
+    system: TokenizerType,
+    user: Option<TokenizerType>,
+    schema: DictionarySchema,
+    space_category_id: Option<CategoryId>,
+}
+
+impl SharedDictionaryTokenizer {
+    /// Fails if the word format of a jpreprocess dictionary cannot be read by this JPreprocess.
+    pub fn new(
+        dictionary: Arc<Dictionary>,
+        user_dictionary: Option<Arc<UserDictionary>>,
+    ) -> Result<Self, DictionaryError> {
+        Self {
+            system: TokenizerType::identify(&dictionary.prefix_dictionary)?,
+            space_category_id: dictionary.character_definition.category_id_by_name("SPACE"),
+            dictionary,
+            user_dictionary: None,
+            user: None,
+            schema: DictionarySchema::default(),
+        }
+        .with_user_dictionary(user_dictionary)
+    }
+
+    /// Sets the schema of lindera dictionaries.
+    pub fn with_schema(self, schema: DictionarySchema) -> Self {
+        Self { schema, ..self }
+    }
+
+    /// Creates a tokenizer with the same system dictionary and another user dictionary.
+    ///
+    /// To use several user dictionaries at once, stack them with
+    /// [`stack_user_dictionaries`](crate::dictionary::user::stack_user_dictionaries).
+    pub fn with_user_dictionary(
+        &self,
+        user_dictionary: Option<Arc<UserDictionary>>,
+    ) -> Result<Self, DictionaryError> {
+        Ok(Self {
+            user: user_dictionary
+                .as_ref()
+                .map(|d| TokenizerType::identify(&d.dict))
+                .transpose()?,
+            user_dictionary,
+            dictionary: self.dictionary.clone(),
+            ..*self
+        })
+    }
+
+    pub fn dictionary(&self) -> &Arc<Dictionary> {
+        &self.dictionary
+    }
+
+    /// Segments the text in the same way as `lindera::segmenter::Segmenter` in normal mode.
+    ///
+    /// Lindera has no segmenter borrowing its dictionary, so this mirrors
+    /// `Segmenter::segment_with_lattice`. `tests::same_as_segmenter` checks that they agree.
+    pub(super) fn segment<'a>(&'a self, text: &'a str) -> Vec<lindera::token::Token<'a>> {
+        let mut lattice = Lattice::default();
+        let mut tokens = Vec::new();
+
+        let mut sentence_start = 0;
+        for sentence in text.split_inclusive(['\n', '\t', '。', '、']) {
+            lattice.set_text(
+                &self.dictionary.prefix_dictionary,
+                &self.user_dictionary.as_ref().map(|d| &d.dict),
+                &self.dictionary.character_definition,
+                &self.dictionary.unknown_dictionary,
+                &self.dictionary.connection_cost_matrix,
+                sentence,
+                &Mode::Normal,
+            );
+
+            let offsets = lattice.tokens_offset();
+            for (i, &(start, word_id)) in offsets.iter().enumerate() {
+                let end = offsets.get(i + 1).map_or(sentence.len(), |&(end, _)| end);
+                let surface = &sentence[start..end];
+
+                // Whitespace is dropped for MeCab compatibility.
+                let is_space = self.space_category_id.is_some_and(|space| {
+                    surface.chars().all(|c| {
+                        self.dictionary
+                            .character_definition
+                            .lookup_categories(c)
+                            .contains(&space)
+                    })
+                });
+                if is_space {
+                    continue;
+                }
+
+                tokens.push(self.token(
+                    surface,
+                    sentence_start + start,
+                    sentence_start + end,
+                    tokens.len(),
+                    word_id,
+                ));
+            }
+
+            sentence_start += sentence.len();
+        }
+
+        tokens
+    }
+
+    /// Creates a token of a word in the dictionaries of this tokenizer.
+    pub(super) fn token<'a>(
+        &'a self,
+        surface: &'a str,
+        byte_start: usize,
+        byte_end: usize,
+        position: usize,
+        word_id: WordId,
+    ) -> lindera::token::Token<'a> {
+        lindera::token::Token::new(
+            Cow::Borrowed(surface),
+            byte_start,
+            byte_end,
+            position,
+            word_id,
+            &self.dictionary,
+            self.user_dictionary.as_deref(),
+        )
+    }
+
+    /// Reads the word entry of a token of this tokenizer.
+    pub(super) fn decode<'a>(
+        &'a self,
+        token: lindera::token::Token<'a>,
+    ) -> JPreprocessResult<DefaultToken<'a>> {
+        decode_token(token, &self.system, self.user.as_ref(), self.schema)
+    }
+}
+
+impl Tokenizer for SharedDictionaryTokenizer {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        decode_tokens(
+            self.segment(text),
+            &self.system,
+            self.user.as_ref(),
+            self.schema,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lindera::dictionary::load_fs_dictionary;
+
+    use super::*;
+
+    #[test]
+    fn same_as_segmenter() {
+        let dictionary = load_fs_dictionary(
+            &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/data/min-dict"),
+        )
+        .unwrap();
+        let segmenter = lindera::segmenter::Segmenter::new(Mode::Normal, dictionary.clone(), None);
+        let tokenizer = SharedDictionaryTokenizer::new(Arc::new(dictionary), None).unwrap();
+
+        let text = "日本語文を\n解析し、\t音声合成 エンジンに　渡せる形式に変換します。\n\n日本語  テスト\t";
+        let summary = |tokens: Vec<lindera::token::Token>| {
+            tokens
+                .into_iter()
+                .map(|token| {
+                    (
+                        token.surface.to_string(),
+                        token.byte_start,
+                        token.byte_end,
+                        token.position,
+                        token.word_id,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let tokens = summary(tokenizer.segment(text));
+        // Half-width spaces, newlines and tabs are in the SPACE category, and dropped.
+        assert!(tokens
+            .iter()
+            .all(|(surface, ..)| !surface.trim_matches([' ', '\n', '\t']).is_empty()));
+        assert_eq!(
+            tokens,
+            summary(segmenter.segment(Cow::Borrowed(text)).unwrap())
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Adapter to use [Sudachi](https://github.com/WorksApplications/sudachi.rs) as the tokenizer.
+//!
+//! This module does not run Sudachi itself: sudachi.rs is not published on crates.io,
+//! so jpreprocess does not depend on it. Instead, implement the adapter trait
+//! [`SudachiAnalyzer`] for your sudachi.rs tokenizer:
+//!
+//! ```rust,ignore
+//! use sudachi::analysis::{stateless_tokenizer::StatelessTokenizer, Mode, Tokenize};
+//!
+//! struct Analyzer<D> {
+//!     tokenizer: StatelessTokenizer<D>,
+//!     mode: Mode,
+//! }
+//!
+//! impl<D: DictionaryAccess> SudachiAnalyzer for Analyzer<D> {
+//!     fn analyze(&self, text: &str) -> JPreprocessResult<Vec<SudachiMorpheme>> {
+//!         let morphemes = self
+//!             .tokenizer
+//!             .tokenize(text, self.mode, false)
+//!             .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+//!         Ok(morphemes
+//!             .iter()
+//!             .map(|m| SudachiMorpheme {
+//!                 surface: m.surface().to_string(),
+//!                 part_of_speech: m.part_of_speech().to_vec(),
+//!                 normalized_form: m.normalized_form().to_string(),
+//!                 reading_form: m.reading_form().to_string(),
+//!                 is_oov: m.is_oov(),
+//!             })
+//!             .collect())
+//!     }
+//! }
+//! ```
+//!
+//! The split mode is chosen by the analyzer.
+//! Since Sudachi dictionaries lack accent information, pronunciations and accents
+//! can be supplied with a [`SideDictionary`]. Otherwise, they are estimated from the POS
+//! as with [`estimate_word_entry`].
+
+use jpreprocess_core::{
+    token::{Token, TokenMetadata, TokenOrigin, Tokenizer},
+    word_details::WordDetails,
+    word_entry::WordEntry,
+    JPreprocessResult,
+};
+
+use super::estimate::estimate_word_entry;
+
+mod pos;
+mod side_dictionary;
+
+pub use pos::{MappedPos, SudachiPosMapper};
+pub use side_dictionary::{SideDictionary, SideEntry};
+
+/// A morpheme returned by Sudachi.
+#[derive(Clone, Debug, Default)]
+pub struct SudachiMorpheme {
+    pub surface: String,
+    /// The six POS fields (e.g. `["名詞", "普通名詞", "一般", "*", "*", "*"]`).
+    pub part_of_speech: Vec<String>,
+    pub normalized_form: String,
+    pub reading_form: String,
+    pub is_oov: bool,
+}
+
+/// Morphological analysis with Sudachi.
+pub trait SudachiAnalyzer {
+    fn analyze(&self, text: &str) -> JPreprocessResult<Vec<SudachiMorpheme>>;
+}
+
+pub struct SudachiTokenizer<A: SudachiAnalyzer> {
+    analyzer: A,
+    pos_mapper: SudachiPosMapper,
+    side_dictionary: Option<SideDictionary>,
+    use_normalized_form: bool,
+}
+
+impl<A: SudachiAnalyzer> SudachiTokenizer<A> {
+    pub fn new(analyzer: A) -> Self {
+        Self {
+            analyzer,
+            pos_mapper: SudachiPosMapper::default(),
+            side_dictionary: None,
+            use_normalized_form: false,
+        }
+    }
+
+    pub fn with_pos_mapper(self, pos_mapper: SudachiPosMapper) -> Self {
+        Self { pos_mapper, ..self }
+    }
+
+    pub fn with_side_dictionary(self, side_dictionary: SideDictionary) -> Self {
+        Self {
+            side_dictionary: Some(side_dictionary),
+            ..self
+        }
+    }
+
+    /// Uses the normalized form (e.g. `附属` -> `付属`) as the string of the node.
+    ///
+    /// The side dictionary is always looked up by the normalized form.
+    pub fn use_normalized_form(self, use_normalized_form: bool) -> Self {
+        Self {
+            use_normalized_form,
+            ..self
+        }
+    }
+
+    /// The accent and the chain rule are estimated from the POS
+    /// unless the word is in the side dictionary.
+    fn word_entry(&self, morpheme: &SudachiMorpheme) -> WordEntry {
+        let mapped = self.pos_mapper.map(&morpheme.part_of_speech);
+
+        let side_entry = self
+            .side_dictionary
+            .as_ref()
+            .and_then(|dict| dict.get(&morpheme.normalized_form, &morpheme.reading_form));
+        let Some(entry) = side_entry else {
+            return estimate_word_entry(mapped, &morpheme.reading_form, &morpheme.reading_form);
+        };
+
+        WordEntry::Single(WordDetails {
+            pos: mapped.pos,
+            ctype: mapped.ctype,
+            cform: mapped.cform,
+            read: (!morpheme.reading_form.is_empty()).then(|| morpheme.reading_form.clone()),
+            pron: entry.pron.clone(),
+            chain_rule: entry.chain_rule.clone(),
+            chain_flag: None,
+        })
+    }
+}
+
+impl<A: SudachiAnalyzer> Tokenizer for SudachiTokenizer<A> {
+    fn tokenize<'a>(&'a self, text: &'a str) -> JPreprocessResult<Vec<impl 'a + Token>> {
+        Ok(self
+            .analyzer
+            .analyze(text)?
+            .into_iter()
+            .map(|morpheme| {
+                let entry = self.word_entry(&morpheme);
+                SudachiToken {
+                    origin: if morpheme.is_oov {
+                        TokenOrigin::Unknown
+                    } else {
+                        TokenOrigin::System
+                    },
+                    string: if self.use_normalized_form {
+                        morpheme.normalized_form
+                    } else {
+                        morpheme.surface
+                    },
+                    entry,
+                }
+            })
+            .collect::<Vec<_>>())
+    }
+}
+
+struct SudachiToken {
+    string: String,
+    entry: WordEntry,
+    origin: TokenOrigin,
+}
+
+impl Token for SudachiToken {
+    fn fetch(&mut self) -> JPreprocessResult<(&str, WordEntry)> {
+        Ok((&self.string, self.entry.clone()))
+    }
+    fn metadata(&self) -> Option<TokenMetadata> {
+        Some(TokenMetadata {
+            origin: self.origin,
+            word_id: None,
+            cost: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::{
+        accent_rule::ChainRules,
+        pos::{Meishi, POS},
+    };
+
+    use super::*;
+
+    struct Fixed(Vec<SudachiMorpheme>);
+    impl SudachiAnalyzer for Fixed {
+        fn analyze(&self, _text: &str) -> JPreprocessResult<Vec<SudachiMorpheme>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn morpheme(surface: &str, normalized: &str, reading: &str) -> SudachiMorpheme {
+        SudachiMorpheme {
+            surface: surface.to_string(),
+            part_of_speech: ["名詞", "普通名詞", "一般", "*", "*", "*"]
+                .map(str::to_string)
+                .to_vec(),
+            normalized_form: normalized.to_string(),
+            reading_form: reading.to_string(),
+            is_oov: false,
+        }
+    }
+
+    #[test]
+    fn tokenize() {
+        let mut side = SideDictionary::new();
+        side.insert("付属", "*", "フゾク", "0/3", "C1").unwrap();
+
+        let tokenizer = SudachiTokenizer::new(Fixed(vec![
+            morpheme("附属", "付属", "フゾク"),
+            morpheme("学校", "学校", "ガッコウ"),
+        ]))
+        .with_side_dictionary(side)
+        .use_normalized_form(true);
+
+        let mut tokens = tokenizer.tokenize("附属学校").unwrap().into_iter();
+
+        let mut token = tokens.next().unwrap();
+        assert_eq!(token.metadata().unwrap().origin, TokenOrigin::System);
+        let (string, entry) = token.fetch().unwrap();
+        assert_eq!(string, "付属");
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pos, POS::Meishi(Meishi::General));
+        assert_eq!(details.pron.to_string(), "フゾク");
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+
+        let mut token = tokens.next().unwrap();
+        let (string, entry) = token.fetch().unwrap();
+        assert_eq!(string, "学校");
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pron.to_string(), "ガッコウ");
+        assert_eq!(details.pron.accent(), 0);
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+
+        // Without the side dictionary, short nouns are accented on the first mora.
+        let tokenizer = SudachiTokenizer::new(Fixed(vec![morpheme("箸", "箸", "ハシ")]));
+        let (_, entry) = tokenizer.tokenize("箸").unwrap()[0].fetch().unwrap();
+        let WordEntry::Single(details) = entry else {
+            panic!("expected single entry");
+        };
+        assert_eq!(details.pron.accent(), 1);
+        assert_eq!(details.chain_rule, ChainRules::new("C1"));
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::tokenizer::unidic::map_unidic_pos;
+pub use crate::tokenizer::unidic::MappedPos;
+
+/// Maps the six POS fields of Sudachi (UniDic schema) onto `POS`, `CType` and `CForm`.
+///
+/// The built-in rules ([`map_unidic_pos`]) cover the POS used by the Sudachi system dictionaries,
+/// and individual POS can be overridden with [`SudachiPosMapper::insert`].
+#[derive(Clone, Debug, Default)]
+pub struct SudachiPosMapper {
+    overrides: HashMap<Vec<String>, MappedPos>,
+}
+
+impl SudachiPosMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the mapping of a POS.
+    ///
+    /// `sudachi_pos` is compared with all six fields of the morpheme's POS.
+    pub fn insert(&mut self, sudachi_pos: &[&str], mapped: MappedPos) {
+        self.overrides
+            .insert(sudachi_pos.iter().map(|s| s.to_string()).collect(), mapped);
+    }
+
+    pub fn map<S: AsRef<str>>(&self, sudachi_pos: &[S]) -> MappedPos {
+        if self.overrides.is_empty() {
+            return map_unidic_pos(sudachi_pos);
+        }
+        let key: Vec<String> = sudachi_pos.iter().map(|s| s.as_ref().to_string()).collect();
+        self.overrides
+            .get(&key)
+            .copied()
+            .unwrap_or_else(|| map_unidic_pos(sudachi_pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::pos::{Meishi, POS};
+
+    use super::*;
+
+    #[test]
+    fn map_override() {
+        let mut mapper = SudachiPosMapper::new();
+        let mapped = MappedPos {
+            pos: POS::Kandoushi,
+            ..Default::default()
+        };
+        mapper.insert(&["名詞", "普通名詞", "一般", "*", "*", "*"], mapped);
+
+        assert_eq!(
+            mapper.map(&["名詞", "普通名詞", "一般", "*", "*", "*"]),
+            mapped
+        );
+        assert_eq!(
+            mapper
+                .map(&["名詞", "普通名詞", "副詞可能", "*", "*", "*"])
+                .pos,
+            POS::Meishi(Meishi::FukushiKanou)
+        );
+    }
+}
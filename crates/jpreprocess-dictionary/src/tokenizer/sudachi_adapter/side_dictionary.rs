@@ -0,0 +1,143 @@
+use std::{collections::HashMap, io::Read};
+
+use jpreprocess_core::{
+    accent_rule::ChainRules,
+    pronunciation::{Pronunciation, PronunciationParseError},
+    JPreprocessResult,
+};
+
+/// Pronunciation and accent of a word, supplied from outside of Sudachi.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SideEntry {
+    pub pron: Pronunciation,
+    pub chain_rule: ChainRules,
+}
+
+/// A dictionary supplying pronunciations and accents to Sudachi morphemes.
+///
+/// Each line of the CSV source has the following columns:
+///
+/// ```text
+/// form,reading,pronunciation,accent/mora_size,chain_rule
+/// 東京,トウキョウ,トーキョー,0/4,C1
+/// ```
+///
+/// `form` is compared with the normalized form (or surface) of the morpheme.
+/// `reading` is compared with the Sudachi reading form to tell homographs apart; `*` matches any reading.
+/// `chain_rule` may be omitted.
+#[derive(Clone, Debug, Default)]
+pub struct SideDictionary {
+    entries: HashMap<String, Vec<(Option<String>, SideEntry)>>,
+}
+
+impl SideDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> JPreprocessResult<Self> {
+        let mut dict = Self::new();
+
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(reader);
+        for record in rdr.records() {
+            let record =
+                record.map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            let field = |i: usize| record.get(i).unwrap_or("*");
+
+            dict.insert(field(0), field(1), field(2), field(3), field(4))?;
+        }
+
+        Ok(dict)
+    }
+
+    /// Adds an entry. See [`SideDictionary`] for the meaning of the arguments.
+    pub fn insert(
+        &mut self,
+        form: &str,
+        reading: &str,
+        pron: &str,
+        acc_morasize: &str,
+        chain_rule: &str,
+    ) -> JPreprocessResult<()> {
+        let (accent, mora_size) = match acc_morasize.split_once('/') {
+            Some((accent, mora_size)) => (accent, Some(mora_size)),
+            None => (acc_morasize, None),
+        };
+        let pron =
+            Pronunciation::parse(pron, accent.parse().map_err(PronunciationParseError::from)?)?;
+
+        if let Some(mora_size) = mora_size {
+            let mora_size = mora_size.parse().map_err(PronunciationParseError::from)?;
+            if mora_size != pron.mora_size() {
+                return Err(
+                    PronunciationParseError::MoraSizeMismatch(mora_size, pron.mora_size()).into(),
+                );
+            }
+        }
+
+        let reading = match reading {
+            "*" | "" => None,
+            reading => Some(reading.to_string()),
+        };
+        self.entries.entry(form.to_string()).or_default().push((
+            reading,
+            SideEntry {
+                pron,
+                chain_rule: ChainRules::new(chain_rule),
+            },
+        ));
+
+        Ok(())
+    }
+
+    /// Finds the entry of the form, preferring the one whose reading matches.
+    pub fn get(&self, form: &str, reading: &str) -> Option<&SideEntry> {
+        let candidates = self.entries.get(form)?;
+        candidates
+            .iter()
+            .find(|(r, _)| r.as_deref() == Some(reading))
+            .or_else(|| candidates.iter().find(|(r, _)| r.is_none()))
+            .map(|(_, entry)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::JPreprocessError;
+
+    use super::*;
+
+    #[test]
+    fn load() {
+        let dict = SideDictionary::from_reader(
+            "東京,トウキョウ,トーキョー,0/4,C1\n辛い,カライ,カライ,2/3\n辛い,*,ツライ,0/3\n"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let tokyo = dict.get("東京", "トウキョウ").unwrap();
+        assert_eq!(tokyo.pron.to_string(), "トーキョー");
+        assert_eq!(tokyo.pron.accent(), 0);
+
+        assert_eq!(dict.get("辛い", "カライ").unwrap().pron.accent(), 2);
+        assert_eq!(
+            dict.get("辛い", "ツライ").unwrap().pron.to_string(),
+            "ツライ"
+        );
+        assert!(dict.get("大阪", "オオサカ").is_none());
+    }
+
+    #[test]
+    fn mora_size_mismatch() {
+        let mut dict = SideDictionary::new();
+        assert!(matches!(
+            dict.insert("東京", "*", "トーキョー", "0/3", "*"),
+            Err(JPreprocessError::PronunciationParseError(
+                PronunciationParseError::MoraSizeMismatch(3, 4)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+//! Mapping of the UniDic part of speech onto the naist-jdic schema.
+
+use std::str::FromStr;
+
+use jpreprocess_core::{cform::CForm, ctype::CType, pos::POS};
+
+/// Part of speech and conjugation mapped onto the naist-jdic schema.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct MappedPos {
+    pub pos: POS,
+    pub ctype: CType,
+    pub cform: CForm,
+}
+
+/// Maps the UniDic POS fields (品詞1-4, 活用型, 活用形) onto [`POS`], [`CType`] and [`CForm`].
+///
+/// Unmapped fields fall back to [`POS::Unknown`], [`CType::None`] and [`CForm::None`].
+pub fn map_unidic_pos<S: AsRef<str>>(fields: &[S]) -> MappedPos {
+    let field = |i: usize| fields.get(i).map(AsRef::as_ref).unwrap_or("*");
+
+    let [g0, g1, g2, g3] = map_pos(field(0), field(1), field(2), field(3));
+    MappedPos {
+        pos: POS::from_strs(g0, g1, g2, g3).unwrap_or(POS::Unknown),
+        ctype: CType::from_str(&map_ctype(field(4))).unwrap_or(CType::None),
+        cform: CForm::from_str(map_cform(field(5))).unwrap_or(CForm::None),
+    }
+}
+
+fn map_pos<'a>(g0: &'a str, g1: &'a str, g2: &'a str, g3: &'a str) -> [&'a str; 4] {
+    match (g0, g1, g2, g3) {
+        ("名詞", "普通名詞", "サ変可能" | "サ変形状詞可能", _) => {
+            ["名詞", "サ変接続", "*", "*"]
+        }
+        ("名詞", "普通名詞", "形状詞可能", _) => ["名詞", "形容動詞語幹", "*", "*"],
+        ("名詞", "普通名詞", "副詞可能", _) => ["名詞", "副詞可能", "*", "*"],
+        ("名詞", "普通名詞", _, _) => ["名詞", "一般", "*", "*"],
+        ("名詞", "固有名詞", "人名", "姓") => ["名詞", "固有名詞", "人名", "姓"],
+        ("名詞", "固有名詞", "人名", "名") => ["名詞", "固有名詞", "人名", "名"],
+        ("名詞", "固有名詞", "人名", _) => ["名詞", "固有名詞", "人名", "一般"],
+        ("名詞", "固有名詞", "地名", "国") => ["名詞", "固有名詞", "地域", "国"],
+        ("名詞", "固有名詞", "地名", _) => ["名詞", "固有名詞", "地域", "一般"],
+        ("名詞", "固有名詞", "組織", _) => ["名詞", "固有名詞", "組織", "*"],
+        ("名詞", "固有名詞", _, _) => ["名詞", "固有名詞", "一般", "*"],
+        ("名詞", "数詞", _, _) => ["名詞", "数", "*", "*"],
+        ("名詞", "助動詞語幹", _, _) => ["名詞", "特殊", "助動詞語幹", "*"],
+        ("名詞", _, _, _) => ["名詞", "一般", "*", "*"],
+        ("代名詞", _, _, _) => ["名詞", "代名詞", "一般", "*"],
+        ("形状詞", "助動詞語幹", _, _) => ["名詞", "特殊", "助動詞語幹", "*"],
+        ("形状詞", _, _, _) => ["名詞", "形容動詞語幹", "*", "*"],
+        ("連体詞", _, _, _) => ["連体詞", "*", "*", "*"],
+        ("副詞", _, _, _) => ["副詞", "一般", "*", "*"],
+        ("接続詞", _, _, _) => ["接続詞", "*", "*", "*"],
+        ("感動詞", "フィラー", _, _) => ["フィラー", "*", "*", "*"],
+        ("感動詞", _, _, _) => ["感動詞", "*", "*", "*"],
+        ("動詞", _, _, _) => ["動詞", "自立", "*", "*"],
+        ("形容詞", _, _, _) => ["形容詞", "自立", "*", "*"],
+        ("助動詞", _, _, _) => ["助動詞", "*", "*", "*"],
+        ("助詞", "格助詞", _, _) => ["助詞", "格助詞", "一般", "*"],
+        ("助詞", "係助詞", _, _) => ["助詞", "係助詞", "*", "*"],
+        ("助詞", "副助詞", _, _) => ["助詞", "副助詞", "*", "*"],
+        ("助詞", "接続助詞", _, _) => ["助詞", "接続助詞", "*", "*"],
+        ("助詞", "終助詞", _, _) => ["助詞", "終助詞", "*", "*"],
+        ("助詞", "準体助詞", _, _) => ["名詞", "非自立", "一般", "*"],
+        ("接頭辞", _, _, _) => ["接頭詞", "名詞接続", "*", "*"],
+        ("接尾辞", "名詞的", "助数詞", _) => ["名詞", "接尾", "助数詞", "*"],
+        ("接尾辞", "名詞的", "サ変可能", _) => ["名詞", "接尾", "サ変接続", "*"],
+        ("接尾辞", "名詞的", "副詞可能", _) => ["名詞", "接尾", "副詞可能", "*"],
+        ("接尾辞", "名詞的", _, _) => ["名詞", "接尾", "一般", "*"],
+        ("接尾辞", "形状詞的", _, _) => ["名詞", "接尾", "形容動詞語幹", "*"],
+        ("接尾辞", "動詞的", _, _) => ["動詞", "接尾", "*", "*"],
+        ("接尾辞", "形容詞的", _, _) => ["形容詞", "接尾", "*", "*"],
+        ("補助記号", "句点", _, _) => ["記号", "句点", "*", "*"],
+        ("補助記号", "読点", _, _) => ["記号", "読点", "*", "*"],
+        ("補助記号", "括弧開", _, _) => ["記号", "括弧開", "*", "*"],
+        ("補助記号", "括弧閉", _, _) => ["記号", "括弧閉", "*", "*"],
+        ("補助記号" | "記号", _, _, _) => ["記号", "一般", "*", "*"],
+        ("空白", _, _, _) => ["記号", "空白", "*", "*"],
+        _ => ["*", "*", "*", "*"],
+    }
+}
+
+fn map_ctype(ctype: &str) -> String {
+    let (major, minor) = ctype.split_once('-').unwrap_or((ctype, ""));
+    match (major, minor) {
+        ("五段", "カ行") => "五段・カ行イ音便".to_string(),
+        ("五段", "ワア行") => "五段・ワ行促音便".to_string(),
+        ("五段", row) => format!("五段・{row}"),
+        ("上一段" | "下一段", _) => "一段".to_string(),
+        ("カ行変格", _) => "カ変・来ル".to_string(),
+        ("サ行変格", _) => "サ変・スル".to_string(),
+        ("形容詞", _) => "形容詞・アウオ段".to_string(),
+        ("助動詞", aux) => format!("特殊・{aux}"),
+        _ => "*".to_string(),
+    }
+}
+
+fn map_cform(cform: &str) -> &str {
+    match cform {
+        "終止形-一般" | "連体形-一般" => "基本形",
+        "終止形-撥音便" | "連体形-撥音便" => "音便基本形",
+        "連用形-一般" => "連用形",
+        "連用形-促音便" | "連用形-イ音便" | "連用形-撥音便" => "連用タ接続",
+        "連用形-ウ音便" => "連用ゴザイ接続",
+        "連用形-ニ" => "連用ニ接続",
+        "未然形-一般" => "未然形",
+        "未然形-撥音便" => "未然特殊",
+        "未然形-セ" => "未然レル接続",
+        "意志推量形" => "未然ウ接続",
+        "仮定形-一般" => "仮定形",
+        "仮定形-融合" => "仮定縮約１",
+        "命令形" => "命令ｅ",
+        "語幹-一般" => "ガル接続",
+        _ => "*",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jpreprocess_core::{
+        ctype::{Five, Special},
+        pos::{Joshi, KakuJoshi, Kigou, Meishi},
+    };
+
+    use super::*;
+
+    #[test]
+    fn map_builtin() {
+        assert_eq!(
+            map_unidic_pos(&["名詞", "普通名詞", "サ変可能", "*", "*", "*"]).pos,
+            POS::Meishi(Meishi::SahenSetsuzoku)
+        );
+        assert_eq!(
+            map_unidic_pos(&["助詞", "格助詞", "*", "*", "*", "*"]).pos,
+            POS::Joshi(Joshi::KakuJoshi(KakuJoshi::General))
+        );
+        assert_eq!(
+            map_unidic_pos(&["補助記号", "読点", "*", "*", "*", "*"]).pos,
+            POS::Kigou(Kigou::Touten)
+        );
+
+        let verb = map_unidic_pos(&["動詞", "一般", "*", "*", "五段-カ行", "連用形-イ音便"]);
+        assert_eq!(verb.ctype, CType::Five(Five::KaI));
+        assert_eq!(verb.cform, CForm::RenyouConjunctionTa);
+
+        let aux = map_unidic_pos(&["助動詞", "*", "*", "*", "助動詞-マス", "終止形-一般"]);
+        assert_eq!(aux.pos, POS::Jodoushi);
+        assert_eq!(aux.ctype, CType::Special(Special::Masu));
+        assert_eq!(aux.cform, CForm::Basic);
+    }
+
+    #[test]
+    fn map_all_pos() {
+        for pos in [
+            ["名詞", "普通名詞", "一般", "*"],
+            ["名詞", "普通名詞", "形状詞可能", "*"],
+            ["名詞", "普通名詞", "副詞可能", "*"],
+            ["名詞", "固有名詞", "人名", "姓"],
+            ["名詞", "固有名詞", "人名", "名"],
+            ["名詞", "固有名詞", "人名", "一般"],
+            ["名詞", "固有名詞", "地名", "国"],
+            ["名詞", "固有名詞", "地名", "一般"],
+            ["名詞", "固有名詞", "組織", "*"],
+            ["名詞", "固有名詞", "一般", "*"],
+            ["名詞", "数詞", "*", "*"],
+            ["名詞", "助動詞語幹", "*", "*"],
+            ["代名詞", "*", "*", "*"],
+            ["形状詞", "一般", "*", "*"],
+            ["形状詞", "タリ", "*", "*"],
+            ["連体詞", "*", "*", "*"],
+            ["副詞", "*", "*", "*"],
+            ["接続詞", "*", "*", "*"],
+            ["感動詞", "フィラー", "*", "*"],
+            ["感動詞", "一般", "*", "*"],
+            ["動詞", "非自立可能", "*", "*"],
+            ["形容詞", "一般", "*", "*"],
+            ["助詞", "係助詞", "*", "*"],
+            ["助詞", "副助詞", "*", "*"],
+            ["助詞", "接続助詞", "*", "*"],
+            ["助詞", "終助詞", "*", "*"],
+            ["助詞", "準体助詞", "*", "*"],
+            ["接頭辞", "*", "*", "*"],
+            ["接尾辞", "名詞的", "一般", "*"],
+            ["接尾辞", "名詞的", "助数詞", "*"],
+            ["接尾辞", "名詞的", "サ変可能", "*"],
+            ["接尾辞", "名詞的", "副詞可能", "*"],
+            ["接尾辞", "形状詞的", "*", "*"],
+            ["接尾辞", "動詞的", "*", "*"],
+            ["接尾辞", "形容詞的", "*", "*"],
+            ["補助記号", "句点", "*", "*"],
+            ["補助記号", "括弧開", "*", "*"],
+            ["補助記号", "括弧閉", "*", "*"],
+            ["補助記号", "一般", "*", "*"],
+            ["記号", "文字", "*", "*"],
+            ["空白", "*", "*", "*"],
+        ] {
+            assert_ne!(map_unidic_pos(&pos).pos, POS::Unknown, "{pos:?}");
+        }
+    }
+}
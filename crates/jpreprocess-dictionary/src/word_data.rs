@@ -26,3 +26,37 @@ pub fn get_word_data<'a>(idx: &[u8], data: &'a [u8], word_id: Option<usize>) ->
         None
     }
 }
+
+/// The identifier of the dictionary at the head of the word data.
+///
+/// It is empty for lindera dictionaries, and may be followed by the surfaces of the words;
+/// see [`get_surfaces`].
+pub fn get_identifier<'a>(idx: &[u8], data: &'a [u8]) -> Option<&'a [u8]> {
+    let preamble = get_word_data(idx, data, None)?;
+    preamble.split(|&b| b == 0).next()
+}
+
+/// The surfaces of the words by word id, recorded after the identifier.
+///
+/// Dictionaries built by lindera or by JPreprocess 0.15 do not record them.
+pub fn get_surfaces<'a>(idx: &[u8], data: &'a [u8]) -> Option<Vec<&'a str>> {
+    let preamble = get_word_data(idx, data, None)?;
+    let start = preamble.iter().position(|&b| b == 0)?;
+    let surfaces = std::str::from_utf8(&preamble[start + 1..]).ok()?;
+    Some(surfaces.split('\0').collect())
+}
+
+/// Encodes the identifier and the surfaces of the words, which come before the words in the word data.
+///
+/// Each surface is preceded by `\0`, so a surface must not contain `\0`.
+pub fn encode_preamble<'a>(
+    identifier: &str,
+    surfaces: impl IntoIterator<Item = &'a str>,
+) -> Vec<u8> {
+    let mut preamble = identifier.as_bytes().to_vec();
+    for surface in surfaces {
+        preamble.push(0);
+        preamble.extend(surface.as_bytes());
+    }
+    preamble
+}
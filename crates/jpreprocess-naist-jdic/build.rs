@@ -7,6 +7,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("cargo:rerun-if-changed=Cargo.toml");
     println!("cargo:rerun-if-changed=prebuilt.json");
     println!("cargo:rerun-if-changed=build.json");
+    println!("cargo:rerun-if-env-changed=JPREPROCESS_NAIST_JDIC_ARCHIVE");
+    println!("cargo:rerun-if-env-changed=JPREPROCESS_NAIST_JDIC_SRC");
 
     fetch_dictionary::download(false).await
 }
@@ -53,6 +55,22 @@ mod fetch_dictionary {
             return Ok(());
         }
 
+        // Local sources for offline builds.
+        if let Some(archive) = std::env::var_os("JPREPROCESS_NAIST_JDIC_ARCHIVE") {
+            println!("cargo:rerun-if-changed={}", Path::new(&archive).display());
+            return from_local_archive(Path::new(&archive), &work_dir, &dict_dir);
+        }
+        if let Some(src_dir) = std::env::var_os("JPREPROCESS_NAIST_JDIC_SRC") {
+            println!("cargo:rerun-if-changed={}", Path::new(&src_dir).display());
+            println!(
+                "Building naist-jdic from {}...",
+                Path::new(&src_dir).display()
+            );
+            let build = BuildConfig::load()?;
+            build.verify_src_dir(Path::new(&src_dir))?;
+            return build.build_from_dir(Path::new(&src_dir), &dict_dir);
+        }
+
         let client = reqwest::ClientBuilder::new()
             .timeout(std::time::Duration::from_secs(30))
             .user_agent(concat!(
@@ -75,27 +93,102 @@ mod fetch_dictionary {
 
         println!("Downloading and building naist-jdic from source...");
 
-        let build = {
-            let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("build.json");
-            let config_data = std::fs::read_to_string(config_path)?;
-            serde_json::from_str::<BuildConfig>(&config_data)?
-        };
-
-        build.build(&client, &work_dir, &dict_dir).await?;
+        BuildConfig::load()?
+            .build(&client, &work_dir, &dict_dir)
+            .await?;
 
         Ok(())
     }
 
+    /// Uses a local copy of the prebuilt archive or the source archive.
+    ///
+    /// The archive is identified by its digest recorded in `prebuilt.json` or `build.json`.
+    fn from_local_archive(
+        archive: &Path,
+        work_dir: &Path,
+        out_dir: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        let bytes = std::fs::read(archive)?;
+        let digest = md5_digest(&bytes);
+
+        if let Ok(prebuilt) = FetchConfig::load("prebuilt.json") {
+            if prebuilt.digest == digest {
+                println!("Using prebuilt naist-jdic at {}.", archive.display());
+                let prebuilt_download_dir = work_dir.join("naist-jdic-prebuilt");
+                prebuilt.unpack(&bytes, &prebuilt_download_dir)?;
+                std::fs::rename(single_subdir(&prebuilt_download_dir)?, out_dir)?;
+                return Ok(());
+            }
+        }
+
+        let build = BuildConfig::load()?;
+        if build.src.digest == digest {
+            println!("Building naist-jdic from {}...", archive.display());
+            let src_download_dir = work_dir.join("src");
+            build.src.unpack(&bytes, &src_download_dir)?;
+            return build.build_from_dir(&single_subdir(&src_download_dir)?, out_dir);
+        }
+
+        Err(Box::new(std::io::Error::other(format!(
+            "MD5 hash of {} ({}) does not match the prebuilt dictionary nor the source (expected {})",
+            archive.display(),
+            digest,
+            build.src.digest
+        ))))
+    }
+
+    /// The directory an archive is unpacked into.
+    fn single_subdir(dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        let name = std::fs::read_dir(dir)?
+            .next()
+            .ok_or_else(|| format!("No directory found in {}", dir.display()))??
+            .file_name();
+        Ok(dir.join(name))
+    }
+
+    fn md5_digest(bytes: &[u8]) -> String {
+        let mut context = md5::Context::new();
+        context.consume(bytes);
+        format!("{:x}", context.finalize())
+    }
+
+    /// MD5 digest of the files the dictionary is built from,
+    /// which are the CSV files, `matrix.def`, `char.def` and `unk.def`.
+    ///
+    /// The names are hashed in sorted order, each followed by the length and the contents of the file.
+    fn src_files_digest(src_dir: &Path) -> Result<String, Box<dyn Error>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(src_dir)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if entry.file_type()?.is_file()
+                && (name.ends_with(".csv")
+                    || ["matrix.def", "char.def", "unk.def"].contains(&&*name))
+            {
+                names.push(name);
+            }
+        }
+        names.sort();
+
+        let mut context = md5::Context::new();
+        for name in names {
+            let bytes = std::fs::read(src_dir.join(&name))?;
+            context.consume(name.as_bytes());
+            context.consume([0]);
+            context.consume((bytes.len() as u64).to_le_bytes());
+            context.consume(&bytes);
+        }
+        Ok(format!("{:x}", context.finalize()))
+    }
+
     async fn download_prebuilt(
         client: &reqwest::Client,
         work_dir: &Path,
         out_dir: &Path,
     ) -> Result<(), Box<dyn Error>> {
-        let prebuilt = {
-            let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("prebuilt.json");
-            let config_data = std::fs::read_to_string(config_path)?;
-            serde_json::from_str::<FetchConfig>(&config_data)?
-        };
+        let prebuilt = FetchConfig::load("prebuilt.json")?;
 
         println!("Attempting to download prebuilt naist-jdic...");
         let prebuilt_download_dir = work_dir.join("naist-jdic-prebuilt");
@@ -103,12 +196,7 @@ mod fetch_dictionary {
 
         println!("Successfully downloaded prebuilt naist-jdic.");
 
-        let prebuilt_name = std::fs::read_dir(&prebuilt_download_dir)?
-            .next()
-            .ok_or("No directory found in prebuilt download dir")??
-            .file_name();
-        let prebuilt_dir = prebuilt_download_dir.join(prebuilt_name);
-        std::fs::rename(&prebuilt_dir, out_dir)?;
+        std::fs::rename(single_subdir(&prebuilt_download_dir)?, out_dir)?;
 
         Ok(())
     }
@@ -117,10 +205,19 @@ mod fetch_dictionary {
     #[derive(Clone, Serialize, Deserialize)]
     struct BuildConfig {
         src: FetchConfig,
+        /// Digest of the source files in the archive, computed by [`src_files_digest`].
+        #[serde(default)]
+        src_files_digest: Option<String>,
         metadata: lindera_dictionary::dictionary::metadata::Metadata,
     }
 
     impl BuildConfig {
+        fn load() -> Result<Self, Box<dyn Error>> {
+            let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("build.json");
+            let config_data = std::fs::read_to_string(config_path)?;
+            Ok(serde_json::from_str(&config_data)?)
+        }
+
         async fn build(
             &self,
             client: &reqwest::Client,
@@ -130,16 +227,48 @@ mod fetch_dictionary {
             let src_download_dir = work_dir.join("src");
             self.src.fetch(client, &src_download_dir).await?;
 
-            let src_name = std::fs::read_dir(&src_download_dir)?
-                .next()
-                .ok_or("No directory found in source download dir")??
-                .file_name();
-            let src_dir = src_download_dir.join(src_name);
+            self.build_from_dir(&single_subdir(&src_download_dir)?, out_dir)
+        }
+
+        /// Verifies a source directory which did not come from the verified archive.
+        fn verify_src_dir(&self, src_dir: &Path) -> Result<(), Box<dyn Error>> {
+            let digest = src_files_digest(src_dir)?;
+            match &self.src_files_digest {
+                Some(expected) if *expected == digest => Ok(()),
+                Some(expected) => Err(Box::new(std::io::Error::other(format!(
+                    "MD5 hash of the source files in {} ({}) does not match the source (expected {})",
+                    src_dir.display(),
+                    digest,
+                    expected
+                )))),
+                None => Err(Box::new(std::io::Error::other(format!(
+                    "The digest of the source files is not recorded in build.json, so {} cannot be verified",
+                    src_dir.display()
+                )))),
+            }
+        }
+
+        /// Builds from the unpacked source archive, whose digest has been verified.
+        fn build_from_dir(&self, src_dir: &Path, out_dir: &Path) -> Result<(), Box<dyn Error>> {
+            let digest = src_files_digest(src_dir)?;
+            match &self.src_files_digest {
+                Some(expected) if *expected != digest => {
+                    return Err(Box::new(std::io::Error::other(format!(
+                        "src_files_digest in build.json ({}) does not match the source archive ({})",
+                        expected, digest
+                    ))));
+                }
+                Some(_) => {}
+                None => println!(
+                    "cargo:warning=src_files_digest is not recorded in build.json; the source files have {}",
+                    digest
+                ),
+            }
 
             jpreprocess_dictionary::dictionary::to_dict::JPreprocessDictionaryBuilder::new(
                 self.metadata.clone(),
             )
-            .build_dictionary(&src_dir, out_dir)?;
+            .build_dictionary(src_dir, out_dir)?;
 
             Ok(())
         }
@@ -152,22 +281,30 @@ mod fetch_dictionary {
     }
 
     impl FetchConfig {
+        fn load(file_name: &str) -> Result<Self, Box<dyn Error>> {
+            let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(file_name);
+            let config_data = std::fs::read_to_string(config_path)?;
+            Ok(serde_json::from_str(&config_data)?)
+        }
+
         async fn fetch(&self, client: &reqwest::Client, path: &Path) -> Result<(), Box<dyn Error>> {
             let response = client.get(&self.url).send().await?;
             let bytes = response.bytes().await?;
 
-            let mut context = md5::Context::new();
-            context.consume(&bytes);
-            let digest = context.finalize();
-            let hash = format!("{:x}", digest);
+            self.unpack(&bytes, path)
+        }
+
+        /// Verifies the digest of the archive and unpacks it.
+        fn unpack(&self, bytes: &[u8], path: &Path) -> Result<(), Box<dyn Error>> {
+            let hash = md5_digest(bytes);
             if hash != self.digest {
                 return Err(Box::new(std::io::Error::other(format!(
-                    "MD5 hash mismatch for prebuilt dictionary: expected {}, got {}",
-                    self.digest, hash
+                    "MD5 hash mismatch for {}: expected {}, got {}",
+                    self.url, self.digest, hash
                 ))));
             }
 
-            let tar = flate2::read::GzDecoder::new(&bytes[..]);
+            let tar = flate2::read::GzDecoder::new(bytes);
             let mut archive = tar::Archive::new(tar);
             archive.unpack(path)?;
 
@@ -26,8 +26,12 @@ impl NJD {
     ) -> JPreprocessResult<Self> {
         let mut nodes = Vec::new();
         for mut token in tokens {
+            let metadata = token.metadata();
             let (string, entry) = token.fetch()?;
-            nodes.extend(NJDNode::load(string, &entry));
+            nodes.extend(NJDNode::load(string, &entry).into_iter().map(|mut node| {
+                node.set_metadata(metadata);
+                node
+            }));
         }
 
         Ok(Self { nodes })
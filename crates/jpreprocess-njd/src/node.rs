@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 use std::fmt::Display;
 
+use jpreprocess_core::token::{TokenMetadata, TokenOrigin};
 use jpreprocess_core::word_entry::WordEntry;
 use jpreprocess_core::{
     cform::CForm, ctype::CType, pos::*, pronunciation::Pronunciation, word_details::WordDetails,
@@ -8,10 +9,19 @@ use jpreprocess_core::{
 
 use jpreprocess_core::accent_rule::ChainRules;
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub struct NJDNode {
     string: String, //*は空文字列として扱う
     details: WordDetails,
+    metadata: Option<TokenMetadata>,
+}
+
+/// The metadata is not compared, as it describes how the node was obtained
+/// rather than the node itself.
+impl PartialEq for NJDNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string && self.details == other.details
+    }
 }
 
 impl Display for NJDNode {
@@ -50,9 +60,19 @@ impl NJDNode {
         entry
             .get_with_string(string)
             .into_iter()
-            .map(|(string, details)| Self { string, details })
+            .map(|(string, details)| Self {
+                string,
+                details,
+                metadata: None,
+            })
             .collect()
     }
+    /// Creates a node which does not come from the tokenizer.
+    pub(crate) fn new_synthesized(s: &str) -> Self {
+        let mut node = Self::new_single(s);
+        node.metadata = Some(TokenMetadata::synthesized());
+        node
+    }
 
     pub fn transfer_from(&mut self, node: &mut Self) {
         self.string.push_str(&node.string);
@@ -128,10 +148,23 @@ impl NJDNode {
     pub fn set_pron(&mut self, pron: Pronunciation) {
         self.details.pron = pron;
     }
+
+    /// Returns the tokenizer information of the node, if available.
+    pub fn get_metadata(&self) -> Option<&TokenMetadata> {
+        self.metadata.as_ref()
+    }
+    pub fn set_metadata(&mut self, metadata: Option<TokenMetadata>) {
+        self.metadata = metadata;
+    }
+    pub fn get_origin(&self) -> Option<TokenOrigin> {
+        self.metadata.map(|metadata| metadata.origin)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use jpreprocess_core::token::TokenOrigin;
+
     use super::NJDNode;
 
     #[test]
@@ -161,6 +194,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn synthesized_node() {
+        let node = NJDNode::new_synthesized("．,名詞,接尾,助数詞,*,*,*,．,テン,テン,0/2,*,-1");
+        assert_eq!(node.get_origin(), Some(TokenOrigin::Synthesized));
+        assert_eq!(node.get_metadata().unwrap().word_id, None);
+
+        // metadata does not affect equality
+        let loaded = NJDNode::new_single("．,名詞,接尾,助数詞,*,*,*,．,テン,テン,0/2,*,-1");
+        assert_eq!(loaded.get_origin(), None);
+        assert_eq!(node, loaded);
+    }
+
     #[test]
     fn test_send() {
         fn assert_send<T: Send>() {}
@@ -54,7 +54,7 @@ pub fn njd_set_digit(njd: &mut NJD) {
                 && prev.get_pos().is_kazu()
                 && next.get_pos().is_kazu()
             {
-                *node = NJDNode::new_single(rule::TEN_FEATURE);
+                *node = NJDNode::new_synthesized(rule::TEN_FEATURE);
                 node.set_chain_flag(true);
                 match prev.get_string() {
                     rule::ZERO1 | rule::ZERO2 => {
@@ -218,9 +218,9 @@ pub fn njd_set_digit(njd: &mut NJD) {
                     && node.get_string() == rule::ONE
                     && next.get_string() == rule::NICHI
                 {
-                    *node = NJDNode::new_single(rule::TSUITACHI);
+                    *node = NJDNode::new_synthesized(rule::TSUITACHI);
                 } else {
-                    *node = NJDNode::new_single(new_node_s);
+                    *node = NJDNode::new_synthesized(new_node_s);
                 }
 
                 next.reset();
@@ -278,10 +278,10 @@ pub fn njd_set_digit(njd: &mut NJD) {
                 _ => (None, None, UnsetPattern::None),
             };
             if let Some(new_node_s) = node_s {
-                *node = NJDNode::new_single(new_node_s);
+                *node = NJDNode::new_synthesized(new_node_s);
             }
             if let Some(new_node_s) = nx1_s {
-                *nx1 = NJDNode::new_single(new_node_s);
+                *nx1 = NJDNode::new_synthesized(new_node_s);
             }
             match unset {
                 UnsetPattern::None => (),
@@ -106,7 +106,7 @@ pub fn njd_digit_sequence(njd: &mut NJD) {
                 let mut converted_nodes = Vec::with_capacity(node.get_string().chars().count());
                 for c in node.get_string().chars() {
                     if let Some(node_str) = UNKNOWN_DICT_DIGITS.get(&c) {
-                        converted_nodes.push(NJDNode::new_single(node_str));
+                        converted_nodes.push(NJDNode::new_synthesized(node_str));
                     } else {
                         break 'unk_digits;
                     }
@@ -252,7 +252,7 @@ impl DigitSequence {
                 if have_digit_in_block && rev_index > 0 {
                     njd.nodes.insert(
                         nodes_index + 1,
-                        NJDNode::new_single(NUMERAL_LIST3[rev_index / 4]),
+                        NJDNode::new_synthesized(NUMERAL_LIST3[rev_index / 4]),
                     );
                     offset += 1;
                 }
@@ -260,11 +260,14 @@ impl DigitSequence {
             } else {
                 match *digit {
                     0 => (),
-                    1 => njd.nodes[nodes_index] = NJDNode::new_single(NUMERAL_LIST2[rev_index % 4]),
+                    1 => {
+                        njd.nodes[nodes_index] =
+                            NJDNode::new_synthesized(NUMERAL_LIST2[rev_index % 4])
+                    }
                     _ => {
                         njd.nodes.insert(
                             nodes_index + 1,
-                            NJDNode::new_single(NUMERAL_LIST2[rev_index % 4]),
+                            NJDNode::new_synthesized(NUMERAL_LIST2[rev_index % 4]),
                         );
                         offset += 1;
                     }
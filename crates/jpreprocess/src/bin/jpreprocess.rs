@@ -15,6 +15,12 @@ struct Cli {
     #[command(flatten)]
     dict: DictionaryArgs,
 
+    /// Memory-map the word data of the system dictionary instead of reading it
+    ///
+    /// The double array and the connection matrix are still read into memory.
+    #[arg(long, requires = "dictionary")]
+    mmap: bool,
+
     /// The location of the user dictionary
     #[arg(short, long)]
     user_dictionary: Option<PathBuf>,
@@ -60,7 +66,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         })
         .transpose()?;
 
-    let jpreprocess = JPreprocess::with_dictionaries(dictionary.load()?, user_dictionary);
+    let dictionary = if cli.mmap {
+        dictionary.load_mmap()?
+    } else {
+        dictionary.load()?
+    };
+    let jpreprocess = JPreprocess::with_dictionaries(dictionary, user_dictionary)?;
 
     let njd_texts: Vec<String> = jpreprocess.text_to_njd(&cli.input)?.into();
     for line in njd_texts {
@@ -81,3 +92,25 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::{error::ErrorKind, Parser};
+
+    use super::Cli;
+
+    #[test]
+    fn mmap_requires_dictionary() {
+        let cli = Cli::try_parse_from(["jpreprocess", "--mmap", "-d", "dict", "text"]).unwrap();
+        assert!(cli.mmap);
+
+        let err = Cli::try_parse_from(["jpreprocess", "--mmap", "text"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+
+        #[cfg(feature = "naist-jdic")]
+        {
+            let err = Cli::try_parse_from(["jpreprocess", "--mmap", "-n", "text"]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+        }
+    }
+}
@@ -1,22 +1,57 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 
 use jpreprocess_core::JPreprocessResult;
+use jpreprocess_dictionary::dictionary::{
+    archive::{
+        load_dictionary_from_bytes, load_dictionary_from_reader, load_user_dictionary_from_bytes,
+    },
+    mecab::{load_mecab_dictionary, load_mecab_dictionary_cached},
+    to_dict::JPreprocessDictionaryBuilder,
+};
 use lindera::dictionary::load_fs_dictionary;
-use lindera_dictionary::dictionary::Dictionary;
+use lindera_dictionary::dictionary::{Dictionary, UserDictionary};
 
 pub mod kind;
 
+#[cfg(doc)]
+use crate::JPreprocess;
+
 /// System dictionary configuration for JPreprocess.
 pub enum SystemDictionaryConfig {
     /// Use self-contained dictionary. This is only valid if appropreate feature is enabled.
     Bundled(kind::JPreprocessDictionaryKind),
     /// Use pre-built external lindera/jpreprocess dictionary. The PathBuf is the path to dictionary.
     ///
-    /// - When you are using lindera dictionary: Normal dictionary cannot be used;
-    ///   it must contain the accent position and accent rule.
-    /// - When you are using jpreprocess dictionary: The JPreprocess version needs to be same as the
-    ///   JPreprocess that built the dictionary.
+    /// - When you are using lindera dictionary: The dictionary should contain the accent position and accent rule.
+    ///   A normal IPADIC or UniDic dictionary can be used with [`JPreprocess::with_dictionaries_and_schema`],
+    ///   which estimates them.
+    /// - When you are using jpreprocess dictionary: The dictionary must be built by a JPreprocess
+    ///   with the same or an older word format version, or by JPreprocess 0.15.
+    ///   Others are reported as `DictionaryError::UnsupportedFormatVersion` or
+    ///   `DictionaryError::UnsupportedLegacyVersion` when JPreprocess is created.
+    ///
+    /// The dictionary can be memory-mapped with [`Self::load_mmap`].
     File(PathBuf),
+    /// Use a compiled MeCab dictionary such as `open_jtalk_dic_utf_8`.
+    ///
+    /// The dictionary is converted into a jpreprocess dictionary when it is loaded, which takes a while.
+    /// To avoid the conversion at runtime, convert it with `dict_tools build --mecab` and use [`Self::File`].
+    Mecab {
+        /// The directory containing `sys.dic`, `unk.dic`, `matrix.bin` and `char.bin`.
+        path: PathBuf,
+        /// The directory to keep the conversion in for later loads.
+        /// If `None`, the dictionary is converted on every load.
+        cache_dir: Option<PathBuf>,
+    },
+    /// Use a dictionary archive built with `dict_tools archive`.
+    ///
+    /// The archive can be embedded with `include_bytes!`, fetched over the network or read from a database.
+    /// An uncompressed archive in static bytes is used without copying.
+    /// A dictionary built by `EmbeddedDictionaryBuilder` is embedded with
+    /// [`embedded_dictionary!`](crate::embedded_dictionary).
+    Bytes(Cow<'static, [u8]>),
+    /// Use a dictionary archive file built with `dict_tools archive`.
+    Archive(PathBuf),
 }
 
 impl SystemDictionaryConfig {
@@ -24,8 +59,188 @@ impl SystemDictionaryConfig {
         let dictionary = match self {
             Self::Bundled(kind) => kind.load(),
             Self::File(dictionary_path) => load_fs_dictionary(dictionary_path.as_path())?,
+            Self::Mecab { path, cache_dir } => match cache_dir {
+                Some(cache_dir) => load_mecab_dictionary_cached(&path, &cache_dir)?,
+                None => load_mecab_dictionary(&path)?,
+            },
+            Self::Bytes(bytes) => load_dictionary_from_bytes(bytes)?,
+            Self::Archive(archive_path) => load_dictionary_from_reader(std::io::BufReader::new(
+                std::fs::File::open(archive_path)?,
+            ))?,
         };
 
         Ok(dictionary)
     }
+
+    /// Same as [`Self::load`], but the word data of a [`Self::File`] dictionary is memory-mapped.
+    ///
+    /// # Shared between processes
+    ///
+    /// The word data (`dict.words` and `dict.wordsidx`) and the word entries (`dict.vals`) are read
+    /// through the page cache, so processes loading the same dictionary share them,
+    /// and word details are decoded only when the words are tokenized.
+    ///
+    /// # Not shared
+    ///
+    /// The double array (`dict.da`) and the connection matrix (`matrix.mtx`) are decoded into
+    /// the memory of each process, and take as much memory as with [`Self::load`].
+    /// The segmenter of lindera takes them only as owned values, so sharing them needs a segmenter
+    /// which reads them in place, and this mode does not provide it.
+    ///
+    /// The files must not be modified while the dictionary is in use.
+    /// The other configurations are loaded as with [`Self::load`].
+    pub fn load_mmap(self) -> JPreprocessResult<Dictionary> {
+        match self {
+            Self::File(dictionary_path) => Ok(Dictionary::load_from_path_with_options(
+                dictionary_path.as_path(),
+                true,
+            )?),
+            config => config.load(),
+        }
+    }
+}
+
+/// User dictionary configuration for JPreprocess.
+pub enum UserDictionaryConfig {
+    /// Use a compiled user dictionary (`.bin`) or its archive built with `dict_tools archive`.
+    File(PathBuf),
+    /// Use a user dictionary source CSV, which is built on load.
+    ///
+    /// Simplified rows (`surface,pronunciation,accent[,POS]`) need the context ids of a system dictionary,
+    /// so build such a CSV with `dict_tools build --user --simplified --system` instead.
+    Csv(PathBuf),
+    /// Use the bytes of a compiled user dictionary or its archive.
+    ///
+    /// A user dictionary built by `EmbeddedDictionaryBuilder` is embedded with
+    /// [`embedded_user_dictionary!`](crate::embedded_user_dictionary).
+    Bytes(Cow<'static, [u8]>),
+}
+
+/// Embeds the system dictionary built by `EmbeddedDictionaryBuilder` in the build script,
+/// and expands to [`SystemDictionaryConfig::Bytes`].
+///
+/// ```rust,ignore
+/// let system = jpreprocess::embedded_dictionary!("custom").load()?;
+/// let user = jpreprocess::embedded_user_dictionary!("custom").load()?;
+/// let jpreprocess = JPreprocess::with_dictionaries(system, Some(user));
+/// ```
+#[macro_export]
+macro_rules! embedded_dictionary {
+    ($name:literal) => {
+        $crate::SystemDictionaryConfig::Bytes(::std::borrow::Cow::Borrowed(include_bytes!(
+            concat!(env!("OUT_DIR"), "/", $name, ".jpdic")
+        )))
+    };
+}
+
+/// Embeds the user dictionary built by `EmbeddedDictionaryBuilder` in the build script,
+/// and expands to [`UserDictionaryConfig::Bytes`].
+#[macro_export]
+macro_rules! embedded_user_dictionary {
+    ($name:literal) => {
+        $crate::UserDictionaryConfig::Bytes(::std::borrow::Cow::Borrowed(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/",
+            $name,
+            ".user.jpdic"
+        ))))
+    };
+}
+
+impl UserDictionaryConfig {
+    pub fn load(self) -> JPreprocessResult<UserDictionary> {
+        let dictionary = match self {
+            Self::File(dictionary_path) => {
+                load_user_dictionary_from_bytes(&std::fs::read(dictionary_path)?)?
+            }
+            Self::Csv(csv_path) => {
+                JPreprocessDictionaryBuilder::default().build_user_dict(csv_path.as_path())?
+            }
+            Self::Bytes(bytes) => load_user_dictionary_from_bytes(&bytes)?,
+        };
+
+        Ok(dictionary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, path::PathBuf};
+
+    use jpreprocess_dictionary::dictionary::archive::{
+        archive_dictionary_dir, archive_user_dictionary, ArchiveCompression,
+    };
+
+    use lindera_dictionary::dictionary::Dictionary;
+
+    use super::{SystemDictionaryConfig, UserDictionaryConfig};
+
+    #[test]
+    fn mmap() {
+        let path = PathBuf::from("../../tests/data/min-dict");
+        let mapped = SystemDictionaryConfig::File(path.clone())
+            .load_mmap()
+            .unwrap();
+        let read = SystemDictionaryConfig::File(path).load().unwrap();
+
+        let (mapped_prefix, read_prefix) = (&mapped.prefix_dictionary, &read.prefix_dictionary);
+        assert_eq!(*mapped_prefix.words_data, *read_prefix.words_data);
+        assert_eq!(*mapped_prefix.words_idx_data, *read_prefix.words_idx_data);
+        assert_eq!(*mapped_prefix.vals_data, *read_prefix.vals_data);
+        assert_eq!(
+            mapped.connection_cost_matrix.costs_data,
+            read.connection_cost_matrix.costs_data
+        );
+
+        let words = |dictionary: &Dictionary, text| {
+            dictionary
+                .prefix_dictionary
+                .prefix(text)
+                .map(|(end, entry)| (end, entry.word_id.id, entry.left_id, entry.word_cost))
+                .collect::<Vec<_>>()
+        };
+        for text in ["音声合成", "日本語", "解析し", "変換します"] {
+            assert!(!words(&read, text).is_empty());
+            assert_eq!(words(&mapped, text), words(&read, text));
+        }
+
+        let text = "日本語文を解析し、音声合成エンジンに渡せる形式に変換します．";
+        assert_eq!(
+            crate::JPreprocess::with_dictionaries(mapped, None)
+                .unwrap()
+                .run_frontend(text)
+                .unwrap(),
+            crate::JPreprocess::with_dictionaries(read, None)
+                .unwrap()
+                .run_frontend(text)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let mut system = Vec::new();
+        archive_dictionary_dir(
+            &PathBuf::from("../../tests/data/min-dict"),
+            ArchiveCompression::Deflate,
+            &mut system,
+        )
+        .unwrap();
+        let dictionary = SystemDictionaryConfig::Bytes(Cow::Owned(system))
+            .load()
+            .unwrap();
+        assert_eq!(dictionary.connection_cost_matrix.forward_size, 17);
+
+        let bin = std::fs::read("../../tests/data/user/user.bin").unwrap();
+        let mut user = Vec::new();
+        archive_user_dictionary(&bin, ArchiveCompression::None, &mut user).unwrap();
+        for config in [
+            UserDictionaryConfig::Bytes(Cow::Owned(user)),
+            UserDictionaryConfig::Bytes(Cow::Owned(bin)),
+            UserDictionaryConfig::File(PathBuf::from("../../tests/data/user/user.bin")),
+        ] {
+            let user = config.load().unwrap();
+            assert!(!user.dict.find_surface("キログラム").is_empty());
+        }
+    }
 }
@@ -11,7 +11,7 @@
 //! # fn main() -> Result<(), Box<dyn Error>> {
 //! # let path = PathBuf::from("../../tests/data/min-dict");
 //! let system = SystemDictionaryConfig::File(path).load()?;
-//! let jpreprocess = JPreprocess::with_dictionaries(system, None);
+//! let jpreprocess = JPreprocess::with_dictionaries(system, None)?;
 //!
 //! let jpcommon_label = jpreprocess
 //!     .extract_fullcontext("日本語文を解析し、音声合成エンジンに渡せる形式に変換します．")?;
@@ -49,7 +49,10 @@ mod normalize_text;
 pub use normalize_text::normalize_text_for_naist_jdic;
 
 pub use jpreprocess_core::error;
-use jpreprocess_core::{token::Tokenizer, *};
+use jpreprocess_core::{
+    token::{DynTokenizer, Tokenizer},
+    *,
+};
 pub use jpreprocess_njd::NJD;
 
 pub struct JPreprocess<T: Tokenizer> {
@@ -153,6 +156,88 @@ impl<T: Tokenizer> JPreprocess<T> {
     }
 }
 
+impl<T: 'static + Tokenizer + Send + Sync> JPreprocess<T> {
+    /// Erases the tokenizer type.
+    ///
+    /// This is useful when the tokenizer is selected at runtime,
+    /// e.g. to keep JPreprocess with different tokenizers in the same variable.
+    pub fn into_dyn(self) -> JPreprocess<Box<dyn DynTokenizer + Send + Sync>> {
+        JPreprocess::from_tokenizer(Box::new(self.tokenizer))
+    }
+}
+
+#[cfg(feature = "vibrato")]
+mod vibrato_impl {
+    use crate::JPreprocess;
+    use jpreprocess_core::JPreprocessResult;
+
+    impl JPreprocess<vibrato::Tokenizer> {
+        /// Creates JPreprocess with a Vibrato dictionary.
+        ///
+        /// The system dictionary and the user lexicon must be built with
+        /// `dict_tools build vibrato` so that the words carry jpreprocess word details.
+        /// Input text is normalized and unknown words are handled in the same way as
+        /// [`with_dictionaries`](#method.with_dictionaries).
+        /// Whitespace is dropped as in MeCab, so the `SPACE` category must be defined
+        /// in the `char.def` of the system dictionary.
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use jpreprocess::*;
+        ///
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// let system = vibrato::Dictionary::read(std::fs::File::open("naist-jdic.dic")?)?;
+        /// let user = std::fs::read("user.csv")?;
+        /// let jpreprocess = JPreprocess::with_vibrato(system, Some(&user))?;
+        /// #     Ok(())
+        /// # }
+        /// ```
+        pub fn with_vibrato(
+            dictionary: vibrato::Dictionary,
+            user_lexicon: Option<&[u8]>,
+        ) -> JPreprocessResult<Self> {
+            let dictionary = match user_lexicon {
+                Some(user_lexicon) => {
+                    dictionary.reset_user_lexicon_from_reader(Some(user_lexicon))?
+                }
+                None => dictionary,
+            };
+
+            Ok(Self::from_tokenizer(
+                vibrato::Tokenizer::new(dictionary).ignore_space(true)?,
+            ))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::JPreprocess;
+
+        #[test]
+        fn vibrato_whitespace() {
+            let dictionary = vibrato::SystemDictionaryBuilder::from_readers(
+                "日本,0,0,100,名詞,固有名詞,地域,国,*,*,日本,ニホン,ニホン,2/3,C1,-1\n".as_bytes(),
+                "1 1\n0 0 0\n".as_bytes(),
+                "DEFAULT 0 1 0\nSPACE 0 1 0\n0x0020 SPACE\n0x3000 SPACE\n".as_bytes(),
+                "DEFAULT,0,0,1000,名詞,一般,*,*,*,*,*\nSPACE,0,0,1000,記号,空白,*,*,*,*,*\n"
+                    .as_bytes(),
+            )
+            .unwrap();
+            // A user lexicon may have only the leading fields.
+            let user = "語,0,0,100,名詞,一般\n";
+            let jpreprocess = JPreprocess::with_vibrato(dictionary, Some(user.as_bytes())).unwrap();
+
+            let njd = jpreprocess.text_to_njd("日本 語　日本").unwrap();
+            let surfaces = njd
+                .nodes
+                .iter()
+                .map(|node| node.get_string())
+                .collect::<Vec<_>>();
+            assert_eq!(surfaces, ["日本", "語", "日本"]);
+        }
+    }
+}
+
 #[cfg(feature = "tokenizer")]
 mod dictionary;
 #[cfg(feature = "tokenizer")]
@@ -161,7 +246,16 @@ pub use default_tokenizer_impl::*;
 #[cfg(feature = "tokenizer")]
 mod default_tokenizer_impl {
     pub use crate::dictionary::*;
-    pub use jpreprocess_dictionary::tokenizer::default::DefaultTokenizer;
+    pub use jpreprocess_dictionary::{
+        dictionary::user::{stack_user_dictionaries, PosPreset, UserDictionaryEditor},
+        tokenizer::{
+            cached::{CacheStats, CachedTokenizer},
+            default::DefaultTokenizer,
+            estimate::DictionarySchema,
+            mutable::MutableUserDictionaryTokenizer,
+            shared::SharedDictionaryTokenizer,
+        },
+    };
     pub use lindera_dictionary::dictionary::{Dictionary, UserDictionary};
 
     use crate::JPreprocess;
@@ -218,7 +312,7 @@ mod default_tokenizer_impl {
                 None => None,
             };
 
-            Ok(Self::with_dictionaries(dictionary, user_dictionary))
+            Self::with_dictionaries(dictionary, user_dictionary)
         }
 
         /// Creates JPreprocess with provided dictionary data.
@@ -227,6 +321,8 @@ mod default_tokenizer_impl {
         /// To build custom dictionaries, download the `.tgz` archive for your environment (e.g. jpreprocess-x86_64-unknown-linux-gnu.tgz for x86_64 linux)
         /// and use the bundled `dict_tools` executable.
         ///
+        /// Fails if a jpreprocess dictionary has a word format this JPreprocess cannot read.
+        ///
         /// ## System dictionary
         ///
         /// ### Example 1: Load from file
@@ -239,7 +335,7 @@ mod default_tokenizer_impl {
         /// # fn main() -> Result<(), Box<dyn Error>> {
         /// #     let path = PathBuf::from("../../tests/data/min-dict");
         /// let system = SystemDictionaryConfig::File(path).load()?;
-        /// let jpreprocess = JPreprocess::with_dictionaries(system, None);
+        /// let jpreprocess = JPreprocess::with_dictionaries(system, None)?;
         /// #     Ok(())
         /// # }
         /// ```
@@ -253,7 +349,7 @@ mod default_tokenizer_impl {
         /// # #[cfg(feature = "naist-jdic")]
         /// # fn main() -> Result<(), Box<dyn Error>> {
         /// let system = SystemDictionaryConfig::Bundled(JPreprocessDictionaryKind::NaistJdic).load()?;
-        /// let jpreprocess = JPreprocess::with_dictionaries(system, None);
+        /// let jpreprocess = JPreprocess::with_dictionaries(system, None)?;
         /// #     Ok(())
         /// # }
         /// # #[cfg(not(feature = "naist-jdic"))]
@@ -274,23 +370,155 @@ mod default_tokenizer_impl {
         /// #     let user_path = PathBuf::from("../../tests/data/user/");
         /// // Binary dictionary built using `dict_tools` or lindera's dictionary builder
         /// let user = UserDictionaryLoader::load_from_bin(user_path.join("user.bin"))?;
-        /// let jpreprocess = JPreprocess::with_dictionaries(system, Some(user));
+        /// let jpreprocess = JPreprocess::with_dictionaries(system, Some(user))?;
         /// #     Ok(())
         /// # }
         /// ```
         pub fn with_dictionaries(
             dictionary: Dictionary,
             user_dictionary: Option<UserDictionary>,
-        ) -> Self {
+        ) -> JPreprocessResult<Self> {
             let tokenizer = lindera::tokenizer::Tokenizer::new(lindera::segmenter::Segmenter::new(
                 lindera_dictionary::mode::Mode::Normal,
                 dictionary,
                 user_dictionary,
             ));
 
-            let tokenizer = DefaultTokenizer::new(tokenizer);
+            let tokenizer = DefaultTokenizer::new(tokenizer)?;
+
+            Ok(Self::from_tokenizer(tokenizer))
+        }
+
+        /// Creates JPreprocess with lindera dictionaries of the given schema.
+        ///
+        /// With [`DictionarySchema::Ipadic`] or [`DictionarySchema::Unidic`], a standard lindera
+        /// IPADIC or UniDic dictionary can be used. As they lack the accent fields, accent type,
+        /// mora size and chain rule are estimated from the POS and the pronunciation.
+        /// Jpreprocess dictionaries are not affected by the schema.
+        ///
+        /// ```rust
+        /// # use std::error::Error;
+        /// # use std::path::PathBuf;
+        /// use jpreprocess::*;
+        ///
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// #     let path = PathBuf::from("../../tests/data/min-dict");
+        /// let system = SystemDictionaryConfig::File(path).load()?;
+        /// let jpreprocess =
+        ///     JPreprocess::with_dictionaries_and_schema(system, None, DictionarySchema::Ipadic)?;
+        /// #     Ok(())
+        /// # }
+        /// ```
+        pub fn with_dictionaries_and_schema(
+            dictionary: Dictionary,
+            user_dictionary: Option<UserDictionary>,
+            schema: DictionarySchema,
+        ) -> JPreprocessResult<Self> {
+            let tokenizer = lindera::tokenizer::Tokenizer::new(lindera::segmenter::Segmenter::new(
+                lindera_dictionary::mode::Mode::Normal,
+                dictionary,
+                user_dictionary,
+            ));
+
+            let tokenizer = DefaultTokenizer::new(tokenizer)?.with_schema(schema);
+
+            Ok(Self::from_tokenizer(tokenizer))
+        }
+    }
+
+    impl JPreprocess<SharedDictionaryTokenizer> {
+        /// Creates JPreprocess with a system dictionary shared with other instances.
+        ///
+        /// Use [`with_user_dictionary`](#method.with_user_dictionary) to process a request
+        /// with its own user dictionary. Several user dictionaries can be stacked by priority
+        /// with [`stack_user_dictionaries`].
+        ///
+        /// ```rust
+        /// # use std::error::Error;
+        /// # use std::path::PathBuf;
+        /// use std::sync::Arc;
+        /// use jpreprocess::*;
+        /// use lindera_dictionary::loader::user_dictionary::UserDictionaryLoader;
+        ///
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// #     let sys_path = PathBuf::from("../../tests/data/min-dict");
+        /// let system = Arc::new(SystemDictionaryConfig::File(sys_path).load()?);
+        /// let jpreprocess = JPreprocess::with_shared_dictionaries(system, None)?;
+        ///
+        /// #     let user_path = PathBuf::from("../../tests/data/user/");
+        /// let user = Arc::new(UserDictionaryLoader::load_from_bin(user_path.join("user.bin"))?);
+        /// let labels = jpreprocess
+        ///     .with_user_dictionary(Some(user))?
+        ///     .extract_fullcontext("音声を合成します")?;
+        /// #     Ok(())
+        /// # }
+        /// ```
+        pub fn with_shared_dictionaries(
+            dictionary: std::sync::Arc<Dictionary>,
+            user_dictionary: Option<std::sync::Arc<UserDictionary>>,
+        ) -> JPreprocessResult<Self> {
+            Ok(Self::from_tokenizer(SharedDictionaryTokenizer::new(
+                dictionary,
+                user_dictionary,
+            )?))
+        }
+
+        /// Creates JPreprocess with the same system dictionary and another user dictionary.
+        ///
+        /// This does not copy the dictionaries.
+        pub fn with_user_dictionary(
+            &self,
+            user_dictionary: Option<std::sync::Arc<UserDictionary>>,
+        ) -> JPreprocessResult<Self> {
+            Ok(Self::from_tokenizer(
+                self.tokenizer.with_user_dictionary(user_dictionary)?,
+            ))
+        }
+    }
 
-            Self::from_tokenizer(tokenizer)
+    impl JPreprocess<MutableUserDictionaryTokenizer> {
+        /// Creates JPreprocess whose user dictionary can be edited while it is in use.
+        ///
+        /// ```rust
+        /// # use std::error::Error;
+        /// # use std::path::PathBuf;
+        /// use jpreprocess::*;
+        ///
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// #     let path = PathBuf::from("../../tests/data/min-dict");
+        /// let system = SystemDictionaryConfig::File(path).load()?;
+        /// let editor = UserDictionaryEditor::new(Default::default())
+        ///     .with_system_dictionary(&system.prefix_dictionary)?;
+        /// let jpreprocess = JPreprocess::with_mutable_user_dictionary(system, editor)?;
+        ///
+        /// jpreprocess.edit_user_dictionary(|editor| {
+        ///     editor.add("音声合成", "オンセイゴーセイ", Some(4), PosPreset::CommonNoun, None)
+        /// })?;
+        /// let njd = jpreprocess.text_to_njd("音声合成")?;
+        /// assert_eq!(njd.nodes.len(), 1);
+        /// #     Ok(())
+        /// # }
+        /// ```
+        pub fn with_mutable_user_dictionary(
+            dictionary: Dictionary,
+            editor: UserDictionaryEditor,
+        ) -> JPreprocessResult<Self> {
+            let tokenizer = MutableUserDictionaryTokenizer::new(
+                SharedDictionaryTokenizer::new(std::sync::Arc::new(dictionary), None)?,
+                editor,
+            )?;
+
+            Ok(Self::from_tokenizer(tokenizer))
+        }
+
+        /// Edits the user dictionary.
+        ///
+        /// See [`MutableUserDictionaryTokenizer::edit`].
+        pub fn edit_user_dictionary<R>(
+            &self,
+            f: impl FnOnce(&mut UserDictionaryEditor) -> lindera::LinderaResult<R>,
+        ) -> JPreprocessResult<R> {
+            Ok(self.tokenizer.edit(f)?)
         }
     }
 
@@ -302,6 +530,216 @@ mod default_tokenizer_impl {
             use jpreprocess_dictionary::tokenizer::default::DefaultTokenizer;
             fn tester<T: Send + Sync>() {}
             tester::<JPreprocess<DefaultTokenizer>>();
+            tester::<JPreprocess<Box<dyn jpreprocess_core::token::DynTokenizer + Send + Sync>>>();
+            tester::<JPreprocess<crate::MutableUserDictionaryTokenizer>>();
+            tester::<JPreprocess<crate::SharedDictionaryTokenizer>>();
+        }
+
+        #[test]
+        fn shared_dictionaries() {
+            use crate::{stack_user_dictionaries, JPreprocess, SystemDictionaryConfig};
+            use jpreprocess_dictionary::dictionary::to_dict::JPreprocessDictionaryBuilder;
+            use std::sync::Arc;
+
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path.clone()).load().unwrap();
+            let shared = JPreprocess::with_shared_dictionaries(Arc::new(system), None).unwrap();
+
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let default = JPreprocess::with_dictionaries(system, None).unwrap();
+
+            let text = "日本語文を解析し、音声合成エンジンに渡せる 形式に変換します．";
+            assert_eq!(
+                shared.run_frontend(text).unwrap(),
+                default.run_frontend(text).unwrap()
+            );
+
+            let builder = JPreprocessDictionaryBuilder::default();
+            let row = |surface, pron, cost| {
+                vec![
+                    surface, "5", "5", cost, "名詞", "一般", "*", "*", "*", "*", "*", pron, pron,
+                    "0/8", "C1", "-1",
+                ]
+            };
+            let tenant = builder
+                .build_user_dict_from_data(vec![row("音声合成", "オンセイゴーセイ", "-5000")])
+                .unwrap();
+            let common = builder
+                .build_user_dict_from_data(vec![
+                    row("音声合成", "オンセーゴーセー", "-5000"),
+                    row("変換エンジン", "ヘンカンエンジン", "-5000"),
+                ])
+                .unwrap();
+            let stacked = Arc::new(stack_user_dictionaries(&[&tenant, &common]).unwrap());
+
+            let overlay = shared.with_user_dictionary(Some(stacked)).unwrap();
+            let njd = overlay.text_to_njd("音声合成エンジン").unwrap();
+            assert_eq!(njd.nodes[0].get_string(), "音声合成");
+            assert_eq!(njd.nodes[0].get_pron().to_string(), "オンセイゴーセイ");
+            let njd = overlay.text_to_njd("変換エンジン").unwrap();
+            assert_eq!(njd.nodes[0].get_string(), "変換エンジン");
+
+            let njd = shared.text_to_njd("音声合成エンジン").unwrap();
+            assert_eq!(njd.nodes[0].get_string(), "音声");
+        }
+
+        #[test]
+        fn mutable_user_dictionary() {
+            use crate::{JPreprocess, PosPreset, SystemDictionaryConfig, UserDictionaryEditor};
+            use jpreprocess_core::{
+                token::{Token, TokenOrigin, Tokenizer},
+                word_line::WordDetailsLine,
+            };
+
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let editor = UserDictionaryEditor::new(Default::default())
+                .with_system_dictionary(&system.prefix_dictionary)
+                .unwrap();
+            let jpreprocess = JPreprocess::with_mutable_user_dictionary(system, editor).unwrap();
+
+            let text = "音声合成エンジン";
+            assert_eq!(jpreprocess.text_to_njd(text).unwrap().nodes.len(), 3);
+
+            std::thread::scope(|s| {
+                let reader = s.spawn(|| {
+                    for _ in 0..20 {
+                        let len = jpreprocess.text_to_njd(text).unwrap().nodes.len();
+                        assert!(len == 2 || len == 3);
+                    }
+                });
+                jpreprocess
+                    .edit_user_dictionary(|editor| {
+                        editor.add(
+                            "音声合成",
+                            "オンセイゴーセイ",
+                            Some(4),
+                            PosPreset::CommonNoun,
+                            Some(-5000),
+                        )
+                    })
+                    .unwrap();
+                reader.join().unwrap();
+            });
+
+            let njd = jpreprocess.text_to_njd(text).unwrap();
+            assert_eq!(njd.nodes[0].get_string(), "音声合成");
+            assert_eq!(njd.nodes[0].get_origin(), Some(TokenOrigin::User));
+
+            // A failed edit leaves the dictionary unchanged.
+            assert!(jpreprocess
+                .edit_user_dictionary(|editor| {
+                    editor.remove("音声合成");
+                    editor.add("合成", "ゴーセイ", Some(9), PosPreset::CommonNoun, None)
+                })
+                .is_err());
+            assert_eq!(jpreprocess.text_to_njd(text).unwrap().nodes.len(), 2);
+
+            // Tokens read the dictionary they were segmented with, even after an edit.
+            let mut tokens = jpreprocess.tokenizer.tokenize(text).unwrap();
+            jpreprocess
+                .edit_user_dictionary(|editor| Ok(editor.remove("音声合成")))
+                .unwrap();
+            assert_eq!(jpreprocess.text_to_njd(text).unwrap().nodes.len(), 3);
+            let (surface, entry) = tokens[0].fetch().unwrap();
+            assert_eq!(surface, "音声合成");
+            assert_eq!(WordDetailsLine::from(&entry).pron, "オンセイゴーセイ");
+        }
+
+        #[test]
+        fn dyn_tokenizer() {
+            use crate::{JPreprocess, SystemDictionaryConfig};
+
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path.clone()).load().unwrap();
+            let jpreprocess = JPreprocess::with_dictionaries(system, None).unwrap();
+
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let dyn_jpreprocess = JPreprocess::with_dictionaries(system, None)
+                .unwrap()
+                .into_dyn();
+
+            let text = "日本語文を解析し、音声合成エンジンに渡せる形式に変換します．";
+            assert_eq!(
+                jpreprocess.run_frontend(text).unwrap(),
+                dyn_jpreprocess.run_frontend(text).unwrap()
+            );
+        }
+
+        #[test]
+        fn token_metadata() {
+            use crate::{JPreprocess, SystemDictionaryConfig};
+            use jpreprocess_core::token::TokenOrigin;
+
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let matrix = system.connection_cost_matrix.clone();
+            let jpreprocess = JPreprocess::with_dictionaries(system, None).unwrap();
+
+            let njd = jpreprocess.text_to_njd("日本語文をテスト").unwrap();
+
+            let nihongo = njd.nodes[0].get_metadata().unwrap();
+            assert_eq!(nihongo.origin, TokenOrigin::System);
+            let cost = nihongo.cost.unwrap();
+            assert_eq!(cost.word_cost, 1204);
+            assert!((cost.left_context_id as u32) < matrix.backward_size);
+            // Lindera does not expose the path cost.
+            assert_eq!(cost.path_cost, None);
+
+            let bun = njd.nodes[1].get_metadata().unwrap();
+            assert_eq!(bun.origin, TokenOrigin::System);
+
+            let test = njd.nodes.last().unwrap();
+            assert_eq!(test.get_string(), "テスト");
+            assert_eq!(test.get_origin(), Some(TokenOrigin::Unknown));
+        }
+
+        #[test]
+        fn token_metadata_whitespace() {
+            use crate::{JPreprocess, SystemDictionaryConfig};
+
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let jpreprocess = JPreprocess::with_dictionaries(system, None).unwrap();
+
+            // The costs of a word do not depend on the whitespace around it.
+            let metadata = |text| {
+                jpreprocess
+                    .text_to_njd(text)
+                    .unwrap()
+                    .nodes
+                    .iter()
+                    .filter(|node| !node.get_string().trim().is_empty())
+                    .map(|node| (node.get_string().to_string(), *node.get_metadata().unwrap()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                metadata("日本語\n文\tを テスト"),
+                metadata("日本語文をテスト")
+            );
+        }
+
+        #[test]
+        fn estimated_accent() {
+            use crate::{DictionarySchema, JPreprocess, SystemDictionaryConfig};
+
+            // min-dict has the accent fields, but only the IPADIC fields are read.
+            let path = std::path::PathBuf::from("../../tests/data/min-dict");
+            let system = SystemDictionaryConfig::File(path).load().unwrap();
+            let jpreprocess =
+                JPreprocess::with_dictionaries_and_schema(system, None, DictionarySchema::Ipadic)
+                    .unwrap();
+
+            let njd = jpreprocess.text_to_njd("日本語文を解析").unwrap();
+            let nihongo = &njd.nodes[0];
+            assert_eq!(nihongo.get_string(), "日本語");
+            assert_eq!(nihongo.get_pron().mora_size(), 4);
+            assert_eq!(nihongo.get_pron().accent(), 0);
+            assert_eq!(nihongo.get_chain_rule().to_string(), "C1");
+
+            assert!(jpreprocess
+                .extract_fullcontext("日本語文を解析し、音声合成エンジンに渡せる形式に変換します．")
+                .is_ok());
         }
     }
 }
@@ -1,11 +1,7 @@
-#[cfg(not(target_family = "wasm"))]
-mod storage_fetcher;
-
 #[cfg(not(target_family = "wasm"))]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     use jpreprocess::*;
-    use std::path::PathBuf;
-    use storage_fetcher::LruTokenizer;
+    use std::{num::NonZeroUsize, path::PathBuf};
 
     let path = match std::env::args().nth(1).map(PathBuf::from) {
         Some(s) if s.is_dir() => s,
@@ -15,7 +11,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let tokenizer = LruTokenizer::new(path)?;
+    let tokenizer = CachedTokenizer::from_dir(&path, NonZeroUsize::new(1000).unwrap())?;
 
     let jpreprocess = JPreprocess::from_tokenizer(tokenizer);
 
@@ -10,7 +10,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let jpreprocess =
-        JPreprocess::with_dictionaries(SystemDictionaryConfig::File(path.unwrap()).load()?, None);
+        JPreprocess::with_dictionaries(SystemDictionaryConfig::File(path.unwrap()).load()?, None)?;
 
     let mut text = String::new();
     while std::io::stdin().read_line(&mut text).is_ok() {
@@ -1,38 +1,17 @@
 #[cfg(not(target_family = "wasm"))]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let path = match std::env::args().nth(1).map(std::path::PathBuf::from) {
-        Some(s) if s.is_dir() => s,
+        Some(s) if s.is_file() => s,
         _ => {
-            eprintln!("Please specify a valid path to original naist-jdic dictionary");
+            eprintln!("Please specify a valid path to vibrato dictionary built by `dict_tools`");
             std::process::exit(-1);
         }
     };
+    let user_lexicon = std::env::args().nth(2).map(std::fs::read).transpose()?;
 
-    let mut csvs = Vec::new();
-    for entry in std::fs::read_dir(&path)? {
-        let Ok(entry) = entry else {
-            continue;
-        };
-        if entry.file_type()?.is_file() && entry.file_name().to_string_lossy().ends_with(".csv") {
-            csvs.extend_from_slice(&std::fs::read(entry.path())?);
-            if csvs.last() != Some(&b'\n') {
-                csvs.push(b'\n');
-            }
-        }
-    }
-
-    let dict = vibrato::SystemDictionaryBuilder::from_readers(
-        std::io::Cursor::new(csvs),
-        std::fs::File::open(path.join("matrix.def"))?,
-        std::fs::File::open(path.join("char.def"))?,
-        std::fs::File::open(path.join("unk.def"))?,
-    )?;
-
-    let tokenizer = vibrato::Tokenizer::new(dict)
-        .ignore_space(true)?
-        .max_grouping_len(24);
+    let dict = vibrato::Dictionary::read(std::fs::File::open(path)?)?;
 
-    let jpreprocess = jpreprocess::JPreprocess::from_tokenizer(tokenizer);
+    let jpreprocess = jpreprocess::JPreprocess::with_vibrato(dict, user_lexicon.as_deref())?;
     let njd =
         jpreprocess.run_frontend("日本語文を解析し、音声合成エンジンに渡せる形式に変換します．")?;
 
@@ -156,7 +156,27 @@ impl JPreprocess {
         };
 
         Ok(Self {
-            inner: jpreprocess::JPreprocess::with_dictionaries(dictionary, user_dictionary),
+            inner: jpreprocess::JPreprocess::with_dictionaries(dictionary, user_dictionary)
+                .map_err(JsError::from)?,
+        })
+    }
+    /// Creates JPreprocess from archives built with `dict_tools archive`.
+    #[wasm_bindgen(js_name = fromArchive)]
+    pub fn from_archive(
+        system_dictionary: Vec<u8>,
+        user_dictionary: Option<Vec<u8>>,
+    ) -> Result<JPreprocess, JsValue> {
+        let dictionary = jpreprocess::SystemDictionaryConfig::Bytes(system_dictionary.into())
+            .load()
+            .map_err(JsError::from)?;
+        let user_dictionary = user_dictionary
+            .map(|bytes| jpreprocess::UserDictionaryConfig::Bytes(bytes.into()).load())
+            .transpose()
+            .map_err(JsError::from)?;
+
+        Ok(Self {
+            inner: jpreprocess::JPreprocess::with_dictionaries(dictionary, user_dictionary)
+                .map_err(JsError::from)?,
         })
     }
     #[wasm_bindgen]
@@ -17,7 +17,7 @@ fn system_dictionary() -> Result<(), Box<dyn Error>> {
     #[cfg(not(feature = "naist-jdic"))]
     let config = SystemDictionaryConfig::File(PathBuf::from("data/dict"));
 
-    let jpreprocess = JPreprocess::with_dictionaries(config.load()?, None);
+    let jpreprocess = JPreprocess::with_dictionaries(config.load()?, None)?;
 
     let njd = jpreprocess.text_to_njd("クーバネティス")?;
 
@@ -58,7 +58,7 @@ fn lindera_user_dictionary() -> Result<(), Box<dyn Error>> {
     let user_dictionary =
         JPreprocessDictionaryBuilder::default().build_user_dict_from_data(rows)?;
 
-    let jpreprocess = JPreprocess::with_dictionaries(dictionary, Some(user_dictionary));
+    let jpreprocess = JPreprocess::with_dictionaries(dictionary, Some(user_dictionary))?;
     let njd = jpreprocess.text_to_njd("クーバネティス")?;
 
     assert_eq!(njd.nodes[0].get_string(), "クーバネティス");